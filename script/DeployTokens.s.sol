@@ -15,10 +15,16 @@ contract DeployRustToken is Script {
         // Deploy WASM RustToken
         bytes memory wasmBytecode = vm.getCode("out/RustToken.wasm/foundry.json");
         console.log("WASM bytecode size:", wasmBytecode.length);
+
+        // constructor(string name, string symbol, uint8 decimals, uint256 initialSupply, address initialOwner, uint256 cap, bool rebasing)
+        bytes memory rustInitCode = abi.encodePacked(
+            wasmBytecode,
+            abi.encode("RustyToken", "RUST", uint8(18), uint256(1_000_000 * 1e18), deployer, uint256(10_000_000 * 1e18), false)
+        );
         
         address rustToken;
         assembly {
-            rustToken := create(0, add(wasmBytecode, 0x20), mload(wasmBytecode))
+            rustToken := create(0, add(rustInitCode, 0x20), mload(rustInitCode))
         }
         
         require(rustToken != address(0), "RustToken deployment failed");
@@ -0,0 +1,115 @@
+//! Role-based access control in the shape of OpenZeppelin's `AccessControl`.
+
+use crate::{emit_event, revert};
+use alloy_sol_types::sol;
+use fluentbase_sdk::{derive::solidity_storage, Address, ContextReader, SharedAPI, B256};
+
+pub const DEFAULT_ADMIN_ROLE: B256 = B256::ZERO;
+
+sol! {
+    event RoleAdminChanged(bytes32 indexed role, bytes32 indexed previousAdminRole, bytes32 indexed newAdminRole);
+    event RoleGranted(bytes32 indexed role, address indexed account, address indexed sender);
+    event RoleRevoked(bytes32 indexed role, address indexed account, address indexed sender);
+
+    error AccessControlUnauthorizedAccount(address account, bytes32 neededRole);
+    error AccessControlBadConfirmation();
+}
+
+solidity_storage! {
+    mapping(B256 => mapping(Address => bool)) Roles;
+    mapping(B256 => B256) RoleAdmin;
+}
+
+pub fn has_role<SDK: SharedAPI>(sdk: &SDK, role: B256, account: Address) -> bool {
+    Roles::get(sdk, role, account)
+}
+
+/// [`DEFAULT_ADMIN_ROLE`], which is zero, until `set_role_admin` says otherwise.
+pub fn get_role_admin<SDK: SharedAPI>(sdk: &SDK, role: B256) -> B256 {
+    RoleAdmin::get(sdk, role)
+}
+
+/// Reverts unless the caller holds `role`.
+pub fn only_role<SDK: SharedAPI>(sdk: &mut SDK, role: B256) {
+    let caller = sdk.context().contract_caller();
+    if !has_role(sdk, role, caller) {
+        revert(
+            sdk,
+            AccessControlUnauthorizedAccount {
+                account: caller,
+                neededRole: role,
+            },
+        );
+    }
+}
+
+pub fn grant_role<SDK: SharedAPI>(sdk: &mut SDK, role: B256, account: Address) {
+    let admin_role = get_role_admin(sdk, role);
+    only_role(sdk, admin_role);
+    grant_role_unchecked(sdk, role, account);
+}
+
+pub fn revoke_role<SDK: SharedAPI>(sdk: &mut SDK, role: B256, account: Address) {
+    let admin_role = get_role_admin(sdk, role);
+    only_role(sdk, admin_role);
+    revoke_role_unchecked(sdk, role, account);
+}
+
+/// `caller_confirmation` must be the caller's own address.
+pub fn renounce_role<SDK: SharedAPI>(sdk: &mut SDK, role: B256, caller_confirmation: Address) {
+    if caller_confirmation != sdk.context().contract_caller() {
+        revert(sdk, AccessControlBadConfirmation {});
+    }
+    revoke_role_unchecked(sdk, role, caller_confirmation);
+}
+
+pub fn set_role_admin<SDK: SharedAPI>(sdk: &mut SDK, role: B256, admin_role: B256) {
+    let previous_admin_role = get_role_admin(sdk, role);
+    RoleAdmin::set(sdk, role, admin_role);
+
+    emit_event(
+        sdk,
+        RoleAdminChanged {
+            role,
+            previousAdminRole: previous_admin_role,
+            newAdminRole: admin_role,
+        },
+    );
+}
+
+/// Grants `role` without checking the caller. Returns whether it was new.
+pub fn grant_role_unchecked<SDK: SharedAPI>(sdk: &mut SDK, role: B256, account: Address) -> bool {
+    if has_role(sdk, role, account) {
+        return false;
+    }
+    Roles::set(sdk, role, account, true);
+
+    let sender = sdk.context().contract_caller();
+    emit_event(
+        sdk,
+        RoleGranted {
+            role,
+            account,
+            sender,
+        },
+    );
+    true
+}
+
+pub fn revoke_role_unchecked<SDK: SharedAPI>(sdk: &mut SDK, role: B256, account: Address) -> bool {
+    if !has_role(sdk, role, account) {
+        return false;
+    }
+    Roles::set(sdk, role, account, false);
+
+    let sender = sdk.context().contract_caller();
+    emit_event(
+        sdk,
+        RoleRevoked {
+            role,
+            account,
+            sender,
+        },
+    );
+    true
+}
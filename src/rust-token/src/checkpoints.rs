@@ -0,0 +1,172 @@
+//! Sorted `(key, value)` histories in storage, shared by snapshots and votes.
+
+use fluentbase_sdk::{derive::solidity_storage, Address, SharedAPI, B256, U256};
+
+solidity_storage! {
+    mapping(B256 => U256) CheckpointCount;
+    mapping(B256 => mapping(U256 => U256)) CheckpointKey;
+    mapping(B256 => mapping(U256 => U256)) CheckpointValue;
+}
+
+/// Handle to a single history, e.g. the snapshotted balance of one account.
+#[derive(Clone, Copy)]
+pub struct Trace(B256);
+
+impl Trace {
+    /// `namespace` separates unrelated histories of the same account; the
+    /// account address fills the low 20 bytes of the id.
+    pub fn new(namespace: u8, account: Address) -> Self {
+        let mut id = B256::ZERO;
+        id.0[0] = namespace;
+        id.0[12..].copy_from_slice(account.as_slice());
+        Self(id)
+    }
+
+    pub fn len<SDK: SharedAPI>(&self, sdk: &SDK) -> U256 {
+        CheckpointCount::get(sdk, self.0)
+    }
+
+    pub fn at<SDK: SharedAPI>(&self, sdk: &SDK, index: U256) -> (U256, U256) {
+        (
+            CheckpointKey::get(sdk, self.0, index),
+            CheckpointValue::get(sdk, self.0, index),
+        )
+    }
+
+    pub fn latest<SDK: SharedAPI>(&self, sdk: &SDK) -> Option<(U256, U256)> {
+        let len = self.len(sdk);
+        if len.is_zero() {
+            return None;
+        }
+        Some(self.at(sdk, len - U256::from(1)))
+    }
+
+    /// Records `value` at `key`, overwriting the last checkpoint when it has
+    /// the same key.
+    pub fn push<SDK: SharedAPI>(&self, sdk: &mut SDK, key: U256, value: U256) {
+        let len = self.len(sdk);
+        if let Some((last_key, _)) = self.latest(sdk) {
+            if last_key == key {
+                CheckpointValue::set(sdk, self.0, len - U256::from(1), value);
+                return;
+            }
+        }
+        CheckpointKey::set(sdk, self.0, len, key);
+        CheckpointValue::set(sdk, self.0, len, value);
+        CheckpointCount::set(sdk, self.0, len + U256::from(1));
+    }
+
+    /// Value of the last checkpoint whose key is `<= key`, or zero if there
+    /// is none.
+    pub fn upper_lookup<SDK: SharedAPI>(&self, sdk: &SDK, key: U256) -> U256 {
+        // First index whose key is > `key`; the answer sits right before it.
+        let (mut low, mut high) = (U256::ZERO, self.len(sdk));
+        while low < high {
+            let mid = (low + high) / U256::from(2);
+            if CheckpointKey::get(sdk, self.0, mid) > key {
+                high = mid;
+            } else {
+                low = mid + U256::from(1);
+            }
+        }
+        if high.is_zero() {
+            U256::ZERO
+        } else {
+            CheckpointValue::get(sdk, self.0, high - U256::from(1))
+        }
+    }
+
+    /// Value of the first checkpoint whose key is `>= key`, or `None` if all
+    /// keys are smaller.
+    pub fn lower_lookup<SDK: SharedAPI>(&self, sdk: &SDK, key: U256) -> Option<U256> {
+        let len = self.len(sdk);
+        let (mut low, mut high) = (U256::ZERO, len);
+        while low < high {
+            let mid = (low + high) / U256::from(2);
+            if CheckpointKey::get(sdk, self.0, mid) < key {
+                low = mid + U256::from(1);
+            } else {
+                high = mid;
+            }
+        }
+        if low == len {
+            None
+        } else {
+            Some(CheckpointValue::get(sdk, self.0, low))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fluentbase_sdk_testing::HostTestingContext;
+
+    fn push(sdk: &mut HostTestingContext, trace: Trace, key: u64, value: u64) {
+        trace.push(sdk, U256::from(key), U256::from(value));
+    }
+
+    #[test]
+    fn lookups_find_the_surrounding_checkpoints() {
+        let mut sdk = HostTestingContext::default();
+        let trace = Trace::new(1, Address::repeat_byte(0x0a));
+        assert_eq!(trace.upper_lookup(&sdk, U256::from(5)), U256::ZERO);
+        assert_eq!(trace.lower_lookup(&sdk, U256::from(5)), None);
+
+        push(&mut sdk, trace, 2, 20);
+        push(&mut sdk, trace, 4, 40);
+        push(&mut sdk, trace, 4, 41);
+        push(&mut sdk, trace, 7, 70);
+        assert_eq!(trace.len(&sdk), U256::from(3));
+        assert_eq!(trace.latest(&sdk), Some((U256::from(7), U256::from(70))));
+
+        for (key, expected) in [
+            (1, 0),
+            (2, 20),
+            (3, 20),
+            (4, 41),
+            (6, 41),
+            (7, 70),
+            (100, 70),
+        ] {
+            assert_eq!(
+                trace.upper_lookup(&sdk, U256::from(key)),
+                U256::from(expected),
+                "upper_lookup({key})"
+            );
+        }
+        for (key, expected) in [
+            (0, Some(20)),
+            (2, Some(20)),
+            (3, Some(41)),
+            (5, Some(70)),
+            (7, Some(70)),
+            (8, None),
+        ] {
+            assert_eq!(
+                trace.lower_lookup(&sdk, U256::from(key)),
+                expected.map(U256::from),
+                "lower_lookup({key})"
+            );
+        }
+    }
+
+    #[test]
+    fn traces_are_independent() {
+        let mut sdk = HostTestingContext::default();
+        let account = Address::repeat_byte(0x0a);
+        push(&mut sdk, Trace::new(1, account), 1, 10);
+        push(&mut sdk, Trace::new(2, account), 1, 20);
+        push(&mut sdk, Trace::new(1, Address::repeat_byte(0x0b)), 1, 30);
+
+        assert_eq!(
+            Trace::new(1, account).upper_lookup(&sdk, U256::from(1)),
+            U256::from(10)
+        );
+        assert_eq!(
+            Trace::new(2, account).upper_lookup(&sdk, U256::from(1)),
+            U256::from(20)
+        );
+        assert_eq!(Trace::new(3, account).len(&sdk), U256::ZERO);
+    }
+}
@@ -0,0 +1,59 @@
+//! Blocklist and allowlist-only mode for regulated deployments.
+
+use crate::{emit_event, error::TokenError};
+use alloy_sol_types::sol;
+use fluentbase_sdk::{derive::solidity_storage, Address, SharedAPI};
+
+sol! {
+    event BlocklistUpdated(address indexed account, bool blocked);
+    event AllowlistUpdated(address indexed account, bool allowed);
+    event AllowlistModeUpdated(bool enabled);
+
+    error ComplianceBlockedAccount(address account);
+    error ComplianceNotAllowlisted(address account);
+    error ComplianceAccountNotBlocked(address account);
+}
+
+solidity_storage! {
+    mapping(Address => bool) Blocked;
+    mapping(Address => bool) Allowed;
+    bool AllowlistOnly;
+}
+
+pub fn is_blocked<SDK: SharedAPI>(sdk: &SDK, account: Address) -> bool {
+    Blocked::get(sdk, account)
+}
+
+pub fn is_allowlisted<SDK: SharedAPI>(sdk: &SDK, account: Address) -> bool {
+    Allowed::get(sdk, account)
+}
+
+pub fn allowlist_only<SDK: SharedAPI>(sdk: &SDK) -> bool {
+    AllowlistOnly::get(sdk)
+}
+
+pub fn set_blocked<SDK: SharedAPI>(sdk: &mut SDK, account: Address, blocked: bool) {
+    Blocked::set(sdk, account, blocked);
+    emit_event(sdk, BlocklistUpdated { account, blocked });
+}
+
+pub fn set_allowlisted<SDK: SharedAPI>(sdk: &mut SDK, account: Address, allowed: bool) {
+    Allowed::set(sdk, account, allowed);
+    emit_event(sdk, AllowlistUpdated { account, allowed });
+}
+
+pub fn set_allowlist_only<SDK: SharedAPI>(sdk: &mut SDK, enabled: bool) {
+    AllowlistOnly::set(sdk, enabled);
+    emit_event(sdk, AllowlistModeUpdated { enabled });
+}
+
+/// Fails for blocked accounts, and for unlisted ones in allowlist-only mode.
+pub fn check<SDK: SharedAPI>(sdk: &SDK, account: Address) -> Result<(), TokenError> {
+    if is_blocked(sdk, account) {
+        return Err(TokenError::BlockedAccount(account));
+    }
+    if allowlist_only(sdk) && !is_allowlisted(sdk, account) {
+        return Err(TokenError::NotAllowlisted(account));
+    }
+    Ok(())
+}
@@ -0,0 +1,53 @@
+//! EIP-712 domain and ECDSA recovery for `permit` and `delegateBySig`.
+
+use crate::error::TokenError;
+use alloc::{borrow::Cow, string::String};
+use alloy_sol_types::Eip712Domain;
+use fluentbase_sdk::{Address, ContextReader, SharedAPI, B256, U256};
+use hex_literal::hex;
+
+/// secp256k1n / 2. Signatures with a higher `s` are malleable (EIP-2).
+const SECP256K1N_HALF: U256 = U256::from_be_bytes(hex!(
+    "7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0"
+));
+
+/// Version `"1"`, the current chain id and this contract's address.
+pub fn domain<SDK: SharedAPI>(sdk: &SDK, name: String) -> Eip712Domain {
+    let context = sdk.context();
+    Eip712Domain::new(
+        Some(Cow::Owned(name)),
+        Some(Cow::Borrowed("1")),
+        Some(U256::from(context.block_chain_id())),
+        Some(context.contract_address()),
+        None,
+    )
+}
+
+/// Rejects high-`s` and malformed signatures like OpenZeppelin's `ECDSA`.
+pub fn recover<SDK: SharedAPI>(
+    digest: B256,
+    v: u8,
+    r: B256,
+    s: B256,
+) -> Result<Address, TokenError> {
+    if U256::from_be_bytes(s.0) > SECP256K1N_HALF {
+        return Err(TokenError::InvalidSignatureS(s));
+    }
+    let rec_id = match v {
+        27 | 28 => v - 27,
+        _ => return Err(TokenError::InvalidSignature),
+    };
+
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(r.as_slice());
+    signature[32..].copy_from_slice(s.as_slice());
+
+    // 0x04 prefix, then the 64-byte point.
+    let public_key =
+        SDK::secp256k1_recover(&digest, &signature, rec_id).ok_or(TokenError::InvalidSignature)?;
+    let signer = Address::from_raw_public_key(&public_key[1..]);
+    if signer == Address::ZERO {
+        return Err(TokenError::InvalidSignature);
+    }
+    Ok(signer)
+}
@@ -0,0 +1,45 @@
+//! ERC-1363 receiver and spender callbacks.
+
+use alloc::vec::Vec;
+use alloy_sol_types::{sol, SolCall};
+use fluentbase_sdk::{Address, Bytes, U256};
+
+sol! {
+    interface IERC1363Receiver {
+        function onTransferReceived(address operator, address from, uint256 value, bytes data) external returns (bytes4);
+    }
+
+    interface IERC1363Spender {
+        function onApprovalReceived(address owner, uint256 value, bytes data) external returns (bytes4);
+    }
+
+    error ERC1363InvalidReceiver(address receiver);
+    error ERC1363InvalidSpender(address spender);
+}
+
+pub fn on_transfer_received_input(
+    operator: Address,
+    from: Address,
+    value: U256,
+    data: Bytes,
+) -> Vec<u8> {
+    IERC1363Receiver::onTransferReceivedCall {
+        operator,
+        from,
+        value,
+        data,
+    }
+    .abi_encode()
+}
+
+pub fn on_approval_received_input(owner: Address, value: U256, data: Bytes) -> Vec<u8> {
+    IERC1363Spender::onApprovalReceivedCall { owner, value, data }.abi_encode()
+}
+
+/// Whether a callback's ABI-encoded `bytes4` output equals `selector`.
+pub fn is_acknowledged(output: &[u8], selector: [u8; 4]) -> bool {
+    output.len() >= 32 && output[..4] == selector
+}
+
+pub const ON_TRANSFER_RECEIVED: [u8; 4] = IERC1363Receiver::onTransferReceivedCall::SELECTOR;
+pub const ON_APPROVAL_RECEIVED: [u8; 4] = IERC1363Spender::onApprovalReceivedCall::SELECTOR;
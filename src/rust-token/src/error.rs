@@ -0,0 +1,275 @@
+use crate::{
+    compliance::{ComplianceAccountNotBlocked, ComplianceBlockedAccount, ComplianceNotAllowlisted},
+    erc1363::{ERC1363InvalidReceiver, ERC1363InvalidSpender},
+    fees::InvalidTransferFee,
+    flash_mint::{ERC3156ExceededMaxLoan, ERC3156InvalidReceiver, ERC3156UnsupportedToken},
+    pausable::EnforcedPause,
+    revert_with_data,
+    shares::{RebasingDisabled, RebasingInvalidTotal},
+    snapshot::ERC20InvalidSnapshotId,
+    vesting::{VestingInvalidSchedule, VestingLockedBalance, VestingScheduleExists},
+    votes::{ERC5805FutureLookup, VotesExpiredSignature},
+};
+use alloc::vec::Vec;
+use alloy_sol_types::{sol, Panic, PanicKind, SolError};
+use fluentbase_sdk::{Address, SharedAPI, B256, U256};
+
+// ERC-6093 custom errors, as used by OpenZeppelin v5 ERC20
+sol! {
+    error ERC20InsufficientBalance(address sender, uint256 balance, uint256 needed);
+    error ERC20InsufficientAllowance(address spender, uint256 allowance, uint256 needed);
+    error ERC20InvalidSender(address sender);
+    error ERC20InvalidReceiver(address receiver);
+    error ERC20InvalidApprover(address approver);
+    error ERC20InvalidSpender(address spender);
+    error ERC20FailedDecreaseAllowance(address spender, uint256 currentAllowance, uint256 requestedDecrease);
+    error ERC20ExceededCap(uint256 increasedSupply, uint256 cap);
+    error ERC20InvalidCap(uint256 cap);
+
+    error ERC2612ExpiredSignature(uint256 deadline);
+    error ERC2612InvalidSigner(address signer, address owner);
+    error ECDSAInvalidSignature();
+    error ECDSAInvalidSignatureS(bytes32 s);
+    error InvalidAccountNonce(address account, uint256 currentNonce);
+    error ERC20BatchLengthMismatch(uint256 recipients, uint256 values);
+    error ERC20InvalidConstructorArgs();
+}
+
+/// Every revert the token can raise, encoded in one place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenError {
+    InsufficientBalance {
+        sender: Address,
+        balance: U256,
+        needed: U256,
+    },
+    InsufficientAllowance {
+        spender: Address,
+        allowance: U256,
+        needed: U256,
+    },
+    InvalidSender(Address),
+    InvalidReceiver(Address),
+    InvalidApprover(Address),
+    InvalidSpender(Address),
+    FailedDecreaseAllowance {
+        spender: Address,
+        current_allowance: U256,
+        requested_decrease: U256,
+    },
+    /// Minting would take the total supply above the cap set at deploy.
+    ExceededCap {
+        increased_supply: U256,
+        cap: U256,
+    },
+    InvalidCap(U256),
+    /// The deploy input is not an ABI-encoded `ConstructorArgs`.
+    InvalidConstructorArgs,
+    ExpiredSignature(U256),
+    InvalidSigner {
+        signer: Address,
+        owner: Address,
+    },
+    InvalidSignature,
+    InvalidSignatureS(B256),
+    InvalidAccountNonce {
+        account: Address,
+        current_nonce: U256,
+    },
+    VotesExpiredSignature(U256),
+    /// Past votes can only be read for blocks that are already final.
+    FutureLookup {
+        timepoint: U256,
+        clock: u64,
+    },
+    /// The ERC-1363 callback reverted or did not return its selector.
+    InvalidERC1363Receiver(Address),
+    InvalidERC1363Spender(Address),
+    UnsupportedFlashToken(Address),
+    ExceededMaxLoan(U256),
+    /// The flash borrower reverted or did not return the ERC-3156 magic value.
+    InvalidFlashReceiver(Address),
+    /// `batchTransfer` needs exactly one value per recipient.
+    BatchLengthMismatch {
+        recipients: usize,
+        values: usize,
+    },
+    /// The fee is above `fees::MAX_FEE_BASIS_POINTS`, or enabled without a
+    /// treasury.
+    InvalidTransferFee {
+        basis_points: U256,
+        treasury: Address,
+    },
+    /// The account is on the compliance blocklist.
+    BlockedAccount(Address),
+    /// Allowlist-only mode is on and the account is not allowlisted.
+    NotAllowlisted(Address),
+    /// Only blocked accounts can have their funds seized.
+    AccountNotBlocked(Address),
+    /// A beneficiary can only have one vesting schedule.
+    VestingScheduleExists(Address),
+    /// Vesting needs a non-zero amount and a cliff within the duration.
+    InvalidVestingSchedule {
+        cliff: u64,
+        duration: u64,
+        amount: U256,
+    },
+    /// The transfer would dip into tokens that are still vesting.
+    LockedBalance {
+        account: Address,
+        unlocked: U256,
+        needed: U256,
+    },
+    /// The token was deployed without rebasing mode.
+    RebasingDisabled,
+    /// A rebase needs shares to spread the supply over, and cannot take a
+    /// non-empty pool to zero.
+    InvalidRebase {
+        total_shares: U256,
+        new_total: U256,
+    },
+    /// Balances cannot change while the token is paused.
+    EnforcedPause,
+    /// Snapshot ids start at 1 and cannot be in the future.
+    InvalidSnapshotId(U256),
+    /// A credit would push a balance, allowance or the total supply past
+    /// `U256::MAX`. Reverts with `Panic(0x11)` like checked Solidity math.
+    ArithmeticOverflow,
+}
+
+impl TokenError {
+    pub fn revert<SDK: SharedAPI>(self, sdk: &mut SDK) -> ! {
+        revert_with_data(sdk, &self.abi_encode())
+    }
+
+    /// The revert data for this error: selector and ABI-encoded arguments.
+    pub fn abi_encode(self) -> Vec<u8> {
+        match self {
+            TokenError::InsufficientBalance {
+                sender,
+                balance,
+                needed,
+            } => encode(ERC20InsufficientBalance {
+                sender,
+                balance,
+                needed,
+            }),
+            TokenError::InsufficientAllowance {
+                spender,
+                allowance,
+                needed,
+            } => encode(ERC20InsufficientAllowance {
+                spender,
+                allowance,
+                needed,
+            }),
+            TokenError::InvalidSender(sender) => encode(ERC20InvalidSender { sender }),
+            TokenError::InvalidReceiver(receiver) => encode(ERC20InvalidReceiver { receiver }),
+            TokenError::InvalidApprover(approver) => encode(ERC20InvalidApprover { approver }),
+            TokenError::InvalidSpender(spender) => encode(ERC20InvalidSpender { spender }),
+            TokenError::FailedDecreaseAllowance {
+                spender,
+                current_allowance,
+                requested_decrease,
+            } => encode(ERC20FailedDecreaseAllowance {
+                spender,
+                currentAllowance: current_allowance,
+                requestedDecrease: requested_decrease,
+            }),
+            TokenError::ExceededCap {
+                increased_supply,
+                cap,
+            } => encode(ERC20ExceededCap {
+                increasedSupply: increased_supply,
+                cap,
+            }),
+            TokenError::InvalidCap(cap) => encode(ERC20InvalidCap { cap }),
+            TokenError::InvalidConstructorArgs => encode(ERC20InvalidConstructorArgs {}),
+            TokenError::ExpiredSignature(deadline) => encode(ERC2612ExpiredSignature { deadline }),
+            TokenError::InvalidSigner { signer, owner } => {
+                encode(ERC2612InvalidSigner { signer, owner })
+            }
+            TokenError::InvalidSignature => encode(ECDSAInvalidSignature {}),
+            TokenError::InvalidSignatureS(s) => encode(ECDSAInvalidSignatureS { s }),
+            TokenError::InvalidAccountNonce {
+                account,
+                current_nonce,
+            } => encode(InvalidAccountNonce {
+                account,
+                currentNonce: current_nonce,
+            }),
+            TokenError::VotesExpiredSignature(expiry) => encode(VotesExpiredSignature { expiry }),
+            TokenError::FutureLookup { timepoint, clock } => encode(ERC5805FutureLookup {
+                timepoint,
+                clock: U256::from(clock).to(),
+            }),
+            TokenError::InvalidERC1363Receiver(receiver) => {
+                encode(ERC1363InvalidReceiver { receiver })
+            }
+            TokenError::InvalidERC1363Spender(spender) => encode(ERC1363InvalidSpender { spender }),
+            TokenError::UnsupportedFlashToken(token) => encode(ERC3156UnsupportedToken { token }),
+            TokenError::ExceededMaxLoan(max_loan) => {
+                encode(ERC3156ExceededMaxLoan { maxLoan: max_loan })
+            }
+            TokenError::InvalidFlashReceiver(receiver) => {
+                encode(ERC3156InvalidReceiver { receiver })
+            }
+            TokenError::BatchLengthMismatch { recipients, values } => {
+                encode(ERC20BatchLengthMismatch {
+                    recipients: U256::from(recipients),
+                    values: U256::from(values),
+                })
+            }
+            TokenError::InvalidTransferFee {
+                basis_points,
+                treasury,
+            } => encode(InvalidTransferFee {
+                basisPoints: basis_points,
+                treasury,
+            }),
+            TokenError::BlockedAccount(account) => encode(ComplianceBlockedAccount { account }),
+            TokenError::NotAllowlisted(account) => encode(ComplianceNotAllowlisted { account }),
+            TokenError::AccountNotBlocked(account) => {
+                encode(ComplianceAccountNotBlocked { account })
+            }
+            TokenError::VestingScheduleExists(beneficiary) => {
+                encode(VestingScheduleExists { beneficiary })
+            }
+            TokenError::InvalidVestingSchedule {
+                cliff,
+                duration,
+                amount,
+            } => encode(VestingInvalidSchedule {
+                cliff,
+                duration,
+                amount,
+            }),
+            TokenError::LockedBalance {
+                account,
+                unlocked,
+                needed,
+            } => encode(VestingLockedBalance {
+                account,
+                unlocked,
+                needed,
+            }),
+            TokenError::RebasingDisabled => encode(RebasingDisabled {}),
+            TokenError::InvalidRebase {
+                total_shares,
+                new_total,
+            } => encode(RebasingInvalidTotal {
+                totalShares: total_shares,
+                newTotalPooled: new_total,
+            }),
+            TokenError::EnforcedPause => encode(EnforcedPause {}),
+            TokenError::InvalidSnapshotId(id) => encode(ERC20InvalidSnapshotId { id }),
+            TokenError::ArithmeticOverflow => encode(Panic {
+                code: U256::from(PanicKind::UnderOverflow as u32),
+            }),
+        }
+    }
+}
+
+fn encode<E: SolError>(error: E) -> Vec<u8> {
+    error.abi_encode()
+}
@@ -0,0 +1,79 @@
+//! Optional fee-on-transfer, paid to a treasury in basis points.
+
+use crate::{emit_event, error::TokenError};
+use alloy_sol_types::sol;
+use fluentbase_sdk::{derive::solidity_storage, Address, SharedAPI, U256};
+
+sol! {
+    event TransferFeeUpdated(uint256 basisPoints, address indexed treasury);
+    event FeeExemptionUpdated(address indexed account, bool exempt);
+
+    error InvalidTransferFee(uint256 basisPoints, address treasury);
+}
+
+solidity_storage! {
+    U256 FeeBasisPoints;
+    Address FeeTreasury;
+    mapping(Address => bool) FeeExempt;
+}
+
+const BASIS_POINTS: u64 = 10_000;
+pub const MAX_FEE_BASIS_POINTS: u64 = 1_000; // 10%
+
+pub fn basis_points<SDK: SharedAPI>(sdk: &SDK) -> U256 {
+    FeeBasisPoints::get(sdk)
+}
+
+pub fn treasury<SDK: SharedAPI>(sdk: &SDK) -> Address {
+    FeeTreasury::get(sdk)
+}
+
+pub fn is_exempt<SDK: SharedAPI>(sdk: &SDK, account: Address) -> bool {
+    FeeExempt::get(sdk, account)
+}
+
+/// A zero fee turns the feature off; any other fee needs a treasury.
+pub fn set_fee<SDK: SharedAPI>(
+    sdk: &mut SDK,
+    basis_points: U256,
+    treasury: Address,
+) -> Result<(), TokenError> {
+    let enabled = !basis_points.is_zero();
+    if basis_points > U256::from(MAX_FEE_BASIS_POINTS) || (enabled && treasury == Address::ZERO) {
+        return Err(TokenError::InvalidTransferFee {
+            basis_points,
+            treasury,
+        });
+    }
+    FeeBasisPoints::set(sdk, basis_points);
+    FeeTreasury::set(sdk, treasury);
+
+    emit_event(
+        sdk,
+        TransferFeeUpdated {
+            basisPoints: basis_points,
+            treasury,
+        },
+    );
+    Ok(())
+}
+
+pub fn set_exempt<SDK: SharedAPI>(sdk: &mut SDK, account: Address, exempt: bool) {
+    FeeExempt::set(sdk, account, exempt);
+    emit_event(sdk, FeeExemptionUpdated { account, exempt });
+}
+
+/// Fee owed on a transfer of `value` from `from` to `to`, rounded down.
+pub fn fee_for<SDK: SharedAPI>(sdk: &SDK, from: Address, to: Address, value: U256) -> U256 {
+    let basis_points = basis_points(sdk);
+    if basis_points.is_zero() {
+        return U256::ZERO;
+    }
+    let treasury = treasury(sdk);
+    if from == treasury || to == treasury || is_exempt(sdk, from) || is_exempt(sdk, to) {
+        return U256::ZERO;
+    }
+    // Split so `value * basis_points` cannot overflow.
+    let basis = U256::from(BASIS_POINTS);
+    value / basis * basis_points + value % basis * basis_points / basis
+}
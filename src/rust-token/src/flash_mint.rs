@@ -0,0 +1,48 @@
+//! ERC-3156 flash mint: the loan is minted to the borrower and burned on repayment.
+
+use alloc::vec::Vec;
+use alloy_sol_types::{sol, SolCall};
+use fluentbase_sdk::{Address, Bytes, B256, U256};
+use hex_literal::hex;
+
+sol! {
+    interface IERC3156FlashBorrower {
+        function onFlashLoan(address initiator, address token, uint256 amount, uint256 fee, bytes data) external returns (bytes32);
+    }
+
+    error ERC3156UnsupportedToken(address token);
+    error ERC3156ExceededMaxLoan(uint256 maxLoan);
+    error ERC3156InvalidReceiver(address receiver);
+}
+
+/// keccak256("ERC3156FlashBorrower.onFlashLoan")
+pub const CALLBACK_SUCCESS: B256 = B256::new(hex!(
+    "439148f0bbc682ca079e46d6e2c2f0c1e3b820f1a291b069d8882abf8cf18dd9"
+));
+
+/// Flash loans are free; the whole repayment is burned.
+pub fn flash_fee(_amount: U256) -> U256 {
+    U256::ZERO
+}
+
+pub fn on_flash_loan_input(
+    initiator: Address,
+    token: Address,
+    amount: U256,
+    fee: U256,
+    data: Bytes,
+) -> Vec<u8> {
+    IERC3156FlashBorrower::onFlashLoanCall {
+        initiator,
+        token,
+        amount,
+        fee,
+        data,
+    }
+    .abi_encode()
+}
+
+/// Whether `output` of `onFlashLoan` is the expected magic value.
+pub fn is_callback_success(output: &[u8]) -> bool {
+    output.len() >= 32 && output[..32] == CALLBACK_SUCCESS[..]
+}
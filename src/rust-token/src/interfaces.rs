@@ -0,0 +1,110 @@
+//! ERC-165 ids of the standard interfaces the token implements, computed from
+//! their `sol!` declarations. A test checks each declared selector is routed.
+
+use alloy_sol_types::sol;
+
+sol! {
+    interface IERC165 {
+        function supportsInterface(bytes4 interfaceId) external view returns (bool);
+    }
+
+    interface IERC20 {
+        function totalSupply() external view returns (uint256);
+        function balanceOf(address account) external view returns (uint256);
+        function transfer(address to, uint256 value) external returns (bool);
+        function allowance(address owner, address spender) external view returns (uint256);
+        function approve(address spender, uint256 value) external returns (bool);
+        function transferFrom(address from, address to, uint256 value) external returns (bool);
+    }
+
+    interface IERC20Metadata {
+        function name() external view returns (string);
+        function symbol() external view returns (string);
+        function decimals() external view returns (uint8);
+    }
+
+    interface IERC20Permit {
+        function permit(address owner, address spender, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external;
+        function nonces(address owner) external view returns (uint256);
+        function DOMAIN_SEPARATOR() external view returns (bytes32);
+    }
+
+    interface IAccessControl {
+        function hasRole(bytes32 role, address account) external view returns (bool);
+        function getRoleAdmin(bytes32 role) external view returns (bytes32);
+        function grantRole(bytes32 role, address account) external;
+        function revokeRole(bytes32 role, address account) external;
+        function renounceRole(bytes32 role, address callerConfirmation) external;
+    }
+
+    interface IERC6372 {
+        function clock() external view returns (uint48);
+        function CLOCK_MODE() external view returns (string);
+    }
+
+    interface IVotes {
+        function getVotes(address account) external view returns (uint256);
+        function getPastVotes(address account, uint256 timepoint) external view returns (uint256);
+        function getPastTotalSupply(uint256 timepoint) external view returns (uint256);
+        function delegates(address account) external view returns (address);
+        function delegate(address delegatee) external;
+        function delegateBySig(address delegatee, uint256 nonce, uint256 expiry, uint8 v, bytes32 r, bytes32 s) external;
+    }
+
+    interface IERC3156FlashLender {
+        function maxFlashLoan(address token) external view returns (uint256);
+        function flashFee(address token, uint256 amount) external view returns (uint256);
+        function flashLoan(address receiver, address token, uint256 amount, bytes data) external returns (bool);
+    }
+
+    interface IERC1363 {
+        function transferAndCall(address to, uint256 value) external returns (bool);
+        function transferAndCall(address to, uint256 value, bytes data) external returns (bool);
+        function transferFromAndCall(address from, address to, uint256 value) external returns (bool);
+        function transferFromAndCall(address from, address to, uint256 value, bytes data) external returns (bool);
+        function approveAndCall(address spender, uint256 value) external returns (bool);
+        function approveAndCall(address spender, uint256 value, bytes data) external returns (bool);
+    }
+}
+
+/// ERC-165 id of an interface: the XOR of all its function selectors.
+const fn interface_id(selectors: &[[u8; 4]]) -> [u8; 4] {
+    let mut id = [0u8; 4];
+    let mut i = 0;
+    while i < selectors.len() {
+        let mut j = 0;
+        while j < 4 {
+            id[j] ^= selectors[i][j];
+            j += 1;
+        }
+        i += 1;
+    }
+    id
+}
+
+/// Selectors of every interface reported by `supportsInterface`.
+pub const INTERFACES: &[&[[u8; 4]]] = &[
+    IERC165::IERC165Calls::SELECTORS,
+    IERC20::IERC20Calls::SELECTORS,
+    IERC20Metadata::IERC20MetadataCalls::SELECTORS,
+    IERC20Permit::IERC20PermitCalls::SELECTORS,
+    IAccessControl::IAccessControlCalls::SELECTORS,
+    IERC6372::IERC6372Calls::SELECTORS,
+    IVotes::IVotesCalls::SELECTORS,
+    IERC3156FlashLender::IERC3156FlashLenderCalls::SELECTORS,
+    IERC1363::IERC1363Calls::SELECTORS,
+];
+
+pub const SUPPORTED_INTERFACES: [[u8; 4]; INTERFACES.len()] = {
+    let mut ids = [[0u8; 4]; INTERFACES.len()];
+    let mut i = 0;
+    while i < INTERFACES.len() {
+        ids[i] = interface_id(INTERFACES[i]);
+        i += 1;
+    }
+    ids
+};
+
+pub fn supports_interface(interface_id: [u8; 4]) -> bool {
+    SUPPORTED_INTERFACES.contains(&interface_id)
+}
@@ -3,24 +3,206 @@
 extern crate alloc;
 extern crate fluentbase_sdk;
 
-use alloc::vec::Vec;
-use alloy_sol_types::{sol, SolEvent};
+mod access_control;
+mod checkpoints;
+mod compliance;
+mod eip712;
+mod erc1363;
+mod error;
+mod fees;
+mod flash_mint;
+mod interfaces;
+mod ownable;
+mod pausable;
+mod shares;
+mod snapshot;
+mod vesting;
+mod votes;
+
+use access_control::DEFAULT_ADMIN_ROLE;
+use alloc::{string::String, vec::Vec};
+use alloy_sol_types::{sol, Eip712Domain, SolError, SolEvent, SolStruct, SolType};
+use error::TokenError;
 use fluentbase_sdk::{
     basic_entrypoint,
-    derive::{router, solidity_storage, Contract},
-    Address, Bytes, ContextReader, SharedAPI, B256, U256,
+    derive::{function_id, router, solidity_storage, Contract},
+    Address, Bytes, ContextReader, ExitCode, FixedBytes, SharedAPI, B256, U256,
 };
+use hex_literal::hex;
 
 pub trait ERC20API {
-    fn symbol(&self) -> Bytes;
-    fn name(&self) -> Bytes;
-    fn decimals(&self) -> U256;
+    fn symbol(&self) -> String;
+    fn name(&self) -> String;
+    fn decimals(&self) -> u8;
     fn total_supply(&self) -> U256;
-    fn balance_of(&self, account: Address) -> U256;
-    fn transfer(&mut self, to: Address, value: U256) -> U256;
+    fn cap(&self) -> U256;
+    fn balance_of(&mut self, account: Address) -> U256;
+    fn transfer(&mut self, to: Address, value: U256) -> bool;
     fn allowance(&self, owner: Address, spender: Address) -> U256;
-    fn approve(&mut self, spender: Address, value: U256) -> U256;
-    fn transfer_from(&mut self, from: Address, to: Address, value: U256) -> U256;
+    fn approve(&mut self, spender: Address, value: U256) -> bool;
+    fn transfer_from(&mut self, from: Address, to: Address, value: U256) -> bool;
+    fn increase_allowance(&mut self, spender: Address, added_value: U256) -> bool;
+    fn decrease_allowance(&mut self, spender: Address, subtracted_value: U256) -> bool;
+    fn mint(&mut self, to: Address, amount: U256);
+    fn burn(&mut self, amount: U256);
+    fn burn_from(&mut self, account: Address, amount: U256);
+    fn owner(&self) -> Address;
+    fn pending_owner(&self) -> Address;
+    fn transfer_ownership(&mut self, new_owner: Address);
+    fn accept_ownership(&mut self);
+    fn renounce_ownership(&mut self);
+    #[allow(clippy::too_many_arguments)]
+    fn permit(
+        &mut self,
+        owner: Address,
+        spender: Address,
+        value: U256,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    );
+    fn nonces(&self, owner: Address) -> U256;
+    fn domain_separator(&self) -> B256;
+    fn paused(&self) -> bool;
+    fn pause(&mut self);
+    fn unpause(&mut self);
+    fn has_role(&self, role: B256, account: Address) -> bool;
+    fn get_role_admin(&self, role: B256) -> B256;
+    fn grant_role(&mut self, role: B256, account: Address);
+    fn revoke_role(&mut self, role: B256, account: Address);
+    fn renounce_role(&mut self, role: B256, caller_confirmation: Address);
+    fn set_role_admin(&mut self, role: B256, admin_role: B256);
+    fn snapshot(&mut self) -> U256;
+    fn balance_of_at(&mut self, account: Address, snapshot_id: U256) -> U256;
+    fn total_supply_at(&mut self, snapshot_id: U256) -> U256;
+    fn clock(&self) -> u64;
+    fn clock_mode(&self) -> String;
+    fn delegates(&self, account: Address) -> Address;
+    fn get_votes(&self, account: Address) -> U256;
+    fn get_past_votes(&mut self, account: Address, timepoint: U256) -> U256;
+    fn get_past_total_supply(&mut self, timepoint: U256) -> U256;
+    fn delegate(&mut self, delegatee: Address);
+    fn delegate_by_sig(
+        &mut self,
+        delegatee: Address,
+        nonce: U256,
+        expiry: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    );
+    fn max_flash_loan(&self, token: Address) -> U256;
+    fn flash_fee(&mut self, token: Address, amount: U256) -> U256;
+    fn flash_loan(&mut self, receiver: Address, token: Address, amount: U256, data: Bytes) -> bool;
+    fn transfer_and_call(&mut self, to: Address, value: U256) -> bool;
+    fn transfer_and_call_with_data(&mut self, to: Address, value: U256, data: Bytes) -> bool;
+    fn transfer_from_and_call(&mut self, from: Address, to: Address, value: U256) -> bool;
+    fn transfer_from_and_call_with_data(
+        &mut self,
+        from: Address,
+        to: Address,
+        value: U256,
+        data: Bytes,
+    ) -> bool;
+    fn approve_and_call(&mut self, spender: Address, value: U256) -> bool;
+    fn approve_and_call_with_data(&mut self, spender: Address, value: U256, data: Bytes) -> bool;
+    fn supports_interface(&self, interface_id: FixedBytes<4>) -> bool;
+    fn batch_transfer(&mut self, recipients: Vec<Address>, values: Vec<U256>) -> bool;
+    fn multicall(&mut self, data: Vec<Bytes>) -> Vec<Bytes>;
+    fn transfer_fee_basis_points(&self) -> U256;
+    fn fee_treasury(&self) -> Address;
+    fn is_fee_exempt(&self, account: Address) -> bool;
+    fn set_transfer_fee(&mut self, basis_points: U256, treasury: Address);
+    fn set_fee_exempt(&mut self, account: Address, exempt: bool);
+    fn is_blocked(&self, account: Address) -> bool;
+    fn is_allowlisted(&self, account: Address) -> bool;
+    fn allowlist_only(&self) -> bool;
+    fn set_blocked(&mut self, account: Address, blocked: bool);
+    fn set_allowlisted(&mut self, account: Address, allowed: bool);
+    fn set_allowlist_only(&mut self, enabled: bool);
+    fn seize(&mut self, from: Address, to: Address, amount: U256);
+    fn create_vesting_schedule(
+        &mut self,
+        beneficiary: Address,
+        start: u64,
+        cliff: u64,
+        duration: u64,
+        amount: U256,
+    );
+    fn vested_amount(&mut self, beneficiary: Address, timestamp: u64) -> U256;
+    fn released(&mut self, beneficiary: Address) -> U256;
+    fn releasable(&mut self, beneficiary: Address) -> U256;
+    fn locked_balance_of(&mut self, account: Address) -> U256;
+    fn release(&mut self) -> U256;
+    fn rebasing(&self) -> bool;
+    fn shares_of(&self, account: Address) -> U256;
+    fn total_shares(&self) -> U256;
+    fn get_shares_by_pooled_token(&mut self, amount: U256) -> U256;
+    fn get_pooled_token_by_shares(&mut self, shares: U256) -> U256;
+    fn rebase(&mut self, new_total: U256);
+}
+
+/// keccak256("MINTER_ROLE")
+pub const MINTER_ROLE: B256 = B256::new(hex!(
+    "9f2df0fed2c77648de5860a4cc508cd0818c85b8b8a1ab4ceeef8d981c8956a6"
+));
+/// keccak256("PAUSER_ROLE")
+pub const PAUSER_ROLE: B256 = B256::new(hex!(
+    "65d7a28e3265b37a6474929f336521b332c1681b933f6cb9f3376673440d862a"
+));
+/// keccak256("SNAPSHOT_ROLE")
+pub const SNAPSHOT_ROLE: B256 = B256::new(hex!(
+    "5fdbd35e8da83ee755d5e62a539e5ed7f47126abede0b8b10f9ea43dc6eed07f"
+));
+/// keccak256("COMPLIANCE_ROLE")
+pub const COMPLIANCE_ROLE: B256 = B256::new(hex!(
+    "442a94f1a1fac79af32856af2a64f63648cfa2ef3b98610a5bb7cbec4cee6985"
+));
+/// keccak256("ORACLE_ROLE")
+pub const ORACLE_ROLE: B256 = B256::new(hex!(
+    "68e79a7bf1e0bc45d0a330c573bc367f9cf464fd326078812f301165fbda4ef1"
+));
+
+/// Granted to the initial owner at deploy and handed over with ownership.
+const OWNER_ROLES: [B256; 6] = [
+    DEFAULT_ADMIN_ROLE,
+    MINTER_ROLE,
+    PAUSER_ROLE,
+    SNAPSHOT_ROLE,
+    COMPLIANCE_ROLE,
+    ORACLE_ROLE,
+];
+
+// Constructor input, ABI-encoded after the WASM bytecode:
+// constructor(string name, string symbol, uint8 decimals, uint256 initialSupply, address initialOwner, uint256 cap, bool rebasing)
+sol! {
+    struct ConstructorArgs {
+        string name;
+        string symbol;
+        uint8 decimals;
+        uint256 initialSupply;
+        address initialOwner;
+        uint256 cap;
+        bool rebasing;
+    }
+}
+
+// EIP-712 messages for `permit` (EIP-2612) and `delegateBySig` (ERC-5805)
+sol! {
+    struct Permit {
+        address owner;
+        address spender;
+        uint256 value;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    struct Delegation {
+        address delegatee;
+        uint256 nonce;
+        uint256 expiry;
+    }
 }
 
 // Define the Transfer and Approval events
@@ -39,9 +221,100 @@ fn emit_event<SDK: SharedAPI, T: SolEvent>(sdk: &mut SDK, event: T) {
     sdk.emit_log(&topics, &data);
 }
 
+fn revert<SDK: SharedAPI, E: SolError>(sdk: &mut SDK, error: E) -> ! {
+    revert_with_data(sdk, &error.abi_encode())
+}
+
+fn revert_with_data<SDK: SharedAPI>(sdk: &mut SDK, data: &[u8]) -> ! {
+    sdk.write(data);
+    sdk.exit(ExitCode::Err)
+}
+
+/// `a * b / denominator` over the full 512-bit product, rounded down, as in
+/// Uniswap's `FullMath.mulDiv`. `None` if the quotient does not fit.
+fn mul_div(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    // The product is `prod1 * 2^256 + prod0`.
+    let mut prod0 = a.wrapping_mul(b);
+    let mm = a.mul_mod(b, U256::MAX);
+    let borrow = if mm < prod0 {
+        U256::from(1)
+    } else {
+        U256::ZERO
+    };
+    let mut prod1 = mm.wrapping_sub(prod0).wrapping_sub(borrow);
+    if prod1.is_zero() {
+        return Some(prod0 / denominator);
+    }
+    if denominator <= prod1 {
+        return None;
+    }
+
+    // Subtract the remainder so the division is exact, then divide out the
+    // powers of two and multiply by the inverse of the odd part mod 2^256.
+    let remainder = a.mul_mod(b, denominator);
+    if remainder > prod0 {
+        prod1 = prod1.wrapping_sub(U256::from(1));
+    }
+    prod0 = prod0.wrapping_sub(remainder);
+    let twos = denominator & denominator.wrapping_neg();
+    let denominator = denominator / twos;
+    prod0 /= twos;
+    let flip = (U256::ZERO.wrapping_sub(twos) / twos).wrapping_add(U256::from(1));
+    prod0 |= prod1.wrapping_mul(flip);
+
+    let mut inverse = denominator.wrapping_mul(U256::from(3)) ^ U256::from(2);
+    for _ in 0..6 {
+        let correction = U256::from(2).wrapping_sub(denominator.wrapping_mul(inverse));
+        inverse = inverse.wrapping_mul(correction);
+    }
+    Some(prod0.wrapping_mul(inverse))
+}
+
+/// Like [`mul_div`], rounded up.
+fn mul_div_up(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    let quotient = mul_div(a, b, denominator)?;
+    if a.mul_mod(b, denominator).is_zero() {
+        return Some(quotient);
+    }
+    quotient.checked_add(U256::from(1))
+}
+
+/// `None` if the call reverted.
+#[cfg(not(test))]
+fn call_contract<SDK: SharedAPI>(sdk: &mut SDK, target: Address, input: &[u8]) -> Option<Bytes> {
+    let result = sdk.call(target, U256::ZERO, input, None);
+    if !result.status.is_ok() {
+        return None;
+    }
+    Some(result.data)
+}
+
+#[cfg(not(test))]
+fn delegate_call_self<SDK: SharedAPI>(sdk: &mut SDK, input: &[u8]) -> Result<Bytes, Bytes> {
+    let target = sdk.context().contract_address();
+    let result = sdk.delegate_call(target, input, None);
+    if !result.status.is_ok() {
+        return Err(result.data);
+    }
+    Ok(result.data)
+}
+
+// The testing context cannot run other contracts, so tests mock them.
+#[cfg(test)]
+use tests::{call_contract, delegate_call_self};
+
 solidity_storage! {
     mapping(Address => U256) Balance;
     mapping(Address => mapping(Address => U256)) Allowance;
+    U256 TotalSupply;
+    Bytes Name;
+    Bytes Symbol;
+    U256 Decimals;
+    U256 Cap;
+    mapping(Address => U256) Nonces;
 }
 
 impl Balance {
@@ -49,9 +322,11 @@ impl Balance {
         sdk: &mut SDK,
         address: Address,
         amount: U256,
-    ) -> Result<(), &'static str> {
+    ) -> Result<(), TokenError> {
         let current_balance = Self::get(sdk, address);
-        let new_balance = current_balance + amount;
+        let new_balance = current_balance
+            .checked_add(amount)
+            .ok_or(TokenError::ArithmeticOverflow)?;
         Self::set(sdk, address, new_balance);
         Ok(())
     }
@@ -59,10 +334,14 @@ impl Balance {
         sdk: &mut SDK,
         address: Address,
         amount: U256,
-    ) -> Result<(), &'static str> {
+    ) -> Result<(), TokenError> {
         let current_balance = Self::get(sdk, address);
         if current_balance < amount {
-            return Err("insufficient balance");
+            return Err(TokenError::InsufficientBalance {
+                sender: address,
+                balance: current_balance,
+                needed: amount,
+            });
         }
         let new_balance = current_balance - amount;
         Self::set(sdk, address, new_balance);
@@ -76,9 +355,11 @@ impl Allowance {
         owner: Address,
         spender: Address,
         amount: U256,
-    ) -> Result<(), &'static str> {
+    ) -> Result<(), TokenError> {
         let current_allowance = Self::get(sdk, owner, spender);
-        let new_allowance = current_allowance + amount;
+        let new_allowance = current_allowance
+            .checked_add(amount)
+            .ok_or(TokenError::ArithmeticOverflow)?;
         Self::set(sdk, owner, spender, new_allowance);
         Ok(())
     }
@@ -87,10 +368,14 @@ impl Allowance {
         owner: Address,
         spender: Address,
         amount: U256,
-    ) -> Result<(), &'static str> {
+    ) -> Result<(), TokenError> {
         let current_allowance = Self::get(sdk, owner, spender);
         if current_allowance < amount {
-            return Err("insufficient allowance");
+            return Err(TokenError::InsufficientAllowance {
+                spender,
+                allowance: current_allowance,
+                needed: amount,
+            });
         }
         let new_allowance = current_allowance - amount;
         Self::set(sdk, owner, spender, new_allowance);
@@ -115,79 +400,2308 @@ struct ERC20<SDK> {
 
 #[router(mode = "solidity")]
 impl<SDK: SharedAPI> ERC20API for ERC20<SDK> {
-    fn symbol(&self) -> Bytes {
-        Bytes::from("RUST")
+    fn symbol(&self) -> String {
+        String::from_utf8(Symbol::get(&self.sdk).to_vec()).unwrap_or_default()
     }
 
-    fn name(&self) -> Bytes {
-        Bytes::from("RustyToken")
+    fn name(&self) -> String {
+        String::from_utf8(Name::get(&self.sdk).to_vec()).unwrap_or_default()
     }
 
-    fn decimals(&self) -> U256 {
-        U256::from(18)
+    fn decimals(&self) -> u8 {
+        Decimals::get(&self.sdk).to::<u8>()
     }
 
     fn total_supply(&self) -> U256 {
-        U256::from_str_radix("1000000000000000000000000", 10).unwrap()
+        TotalSupply::get(&self.sdk)
     }
 
-    fn balance_of(&self, account: Address) -> U256 {
-        Balance::get(&self.sdk, account)
+    fn cap(&self) -> U256 {
+        Cap::get(&self.sdk)
     }
 
-    fn transfer(&mut self, to: Address, value: U256) -> U256 {
-        let from = self.sdk.context().contract_caller();
-
-        Balance::subtract(&mut self.sdk, from, value).unwrap_or_else(|err| panic!("{}", err));
-        Balance::add(&mut self.sdk, to, value).unwrap_or_else(|err| panic!("{}", err));
+    // `&mut self` so a conversion overflow can revert, as in `balance_of_at`.
+    fn balance_of(&mut self, account: Address) -> U256 {
+        self.balance(account)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk))
+    }
 
-        emit_event(&mut self.sdk, Transfer { from, to, value });
-        U256::from(1)
+    fn transfer(&mut self, to: Address, value: U256) -> bool {
+        let from = self.sdk.context().contract_caller();
+        self.transfer_tokens(from, to, value)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+        true
     }
 
     fn allowance(&self, owner: Address, spender: Address) -> U256 {
         Allowance::get(&self.sdk, owner, spender)
     }
 
-    fn approve(&mut self, spender: Address, value: U256) -> U256 {
+    fn approve(&mut self, spender: Address, value: U256) -> bool {
         let owner = self.sdk.context().contract_caller();
-        Allowance::set(&mut self.sdk, owner, spender, value);
-        emit_event(
-            &mut self.sdk,
-            Approval {
-                owner,
-                spender,
-                value,
-            },
-        );
-        U256::from(1)
+        self.approve_tokens(owner, spender, value)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+        true
     }
 
-    fn transfer_from(&mut self, from: Address, to: Address, value: U256) -> U256 {
+    fn transfer_from(&mut self, from: Address, to: Address, value: U256) -> bool {
         let spender = self.sdk.context().contract_caller();
+        compliance::check(&self.sdk, spender)
+            .and_then(|()| self.spend_allowance(from, spender, value))
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+        self.transfer_tokens(from, to, value)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+        true
+    }
 
-        let current_allowance = Allowance::get(&self.sdk, from, spender);
-        if current_allowance < value {
-            panic!("insufficient allowance");
+    fn increase_allowance(&mut self, spender: Address, added_value: U256) -> bool {
+        let owner = self.sdk.context().contract_caller();
+        self.adjust_allowance(owner, spender, added_value, true)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+        true
+    }
+
+    fn decrease_allowance(&mut self, spender: Address, subtracted_value: U256) -> bool {
+        let owner = self.sdk.context().contract_caller();
+        self.adjust_allowance(owner, spender, subtracted_value, false)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+        true
+    }
+
+    fn mint(&mut self, to: Address, amount: U256) {
+        access_control::only_role(&mut self.sdk, MINTER_ROLE);
+        self.mint_tokens(to, amount)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+    }
+
+    fn burn(&mut self, amount: U256) {
+        let account = self.sdk.context().contract_caller();
+        self.burn_tokens(account, amount)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+    }
+
+    fn burn_from(&mut self, account: Address, amount: U256) {
+        let spender = self.sdk.context().contract_caller();
+        compliance::check(&self.sdk, spender)
+            .and_then(|()| self.spend_allowance(account, spender, amount))
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+        self.burn_tokens(account, amount)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+    }
+
+    fn owner(&self) -> Address {
+        ownable::owner(&self.sdk)
+    }
+
+    fn pending_owner(&self) -> Address {
+        ownable::pending_owner(&self.sdk)
+    }
+
+    fn transfer_ownership(&mut self, new_owner: Address) {
+        ownable::start_ownership_transfer(&mut self.sdk, new_owner);
+    }
+
+    fn accept_ownership(&mut self) {
+        let previous_owner = ownable::owner(&self.sdk);
+        ownable::accept_ownership(&mut self.sdk);
+        let new_owner = ownable::owner(&self.sdk);
+        self.hand_over_roles(previous_owner, new_owner);
+    }
+
+    fn renounce_ownership(&mut self) {
+        let previous_owner = ownable::owner(&self.sdk);
+        ownable::renounce_ownership(&mut self.sdk);
+        self.hand_over_roles(previous_owner, Address::ZERO);
+    }
+
+    fn permit(
+        &mut self,
+        owner: Address,
+        spender: Address,
+        value: U256,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) {
+        let now = U256::from(self.sdk.context().block_timestamp());
+        if now > deadline {
+            TokenError::ExpiredSignature(deadline).revert(&mut self.sdk);
         }
 
-        Allowance::subtract(&mut self.sdk, from, spender, value)
-            .unwrap_or_else(|err| panic!("{}", err));
-        Balance::subtract(&mut self.sdk, from, value).unwrap_or_else(|err| panic!("{}", err));
-        Balance::add(&mut self.sdk, to, value).unwrap_or_else(|err| panic!("{}", err));
+        let nonce = self.use_nonce(owner);
+        let digest = Permit {
+            owner,
+            spender,
+            value,
+            nonce,
+            deadline,
+        }
+        .eip712_signing_hash(&self.eip712_domain());
 
-        emit_event(&mut self.sdk, Transfer { from, to, value });
-        U256::from(1)
+        let signer =
+            eip712::recover::<SDK>(digest, v, r, s).unwrap_or_else(|err| err.revert(&mut self.sdk));
+        if signer != owner {
+            TokenError::InvalidSigner { signer, owner }.revert(&mut self.sdk);
+        }
+
+        self.approve_tokens(owner, spender, value)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+    }
+
+    fn nonces(&self, owner: Address) -> U256 {
+        Nonces::get(&self.sdk, owner)
+    }
+
+    #[function_id("DOMAIN_SEPARATOR()")]
+    fn domain_separator(&self) -> B256 {
+        self.eip712_domain().separator()
+    }
+
+    fn paused(&self) -> bool {
+        pausable::paused(&self.sdk)
+    }
+
+    fn pause(&mut self) {
+        access_control::only_role(&mut self.sdk, PAUSER_ROLE);
+        pausable::pause(&mut self.sdk);
+    }
+
+    fn unpause(&mut self) {
+        access_control::only_role(&mut self.sdk, PAUSER_ROLE);
+        pausable::unpause(&mut self.sdk);
+    }
+
+    fn has_role(&self, role: B256, account: Address) -> bool {
+        access_control::has_role(&self.sdk, role, account)
+    }
+
+    fn get_role_admin(&self, role: B256) -> B256 {
+        access_control::get_role_admin(&self.sdk, role)
+    }
+
+    fn grant_role(&mut self, role: B256, account: Address) {
+        access_control::grant_role(&mut self.sdk, role, account);
+    }
+
+    fn revoke_role(&mut self, role: B256, account: Address) {
+        access_control::revoke_role(&mut self.sdk, role, account);
+    }
+
+    fn renounce_role(&mut self, role: B256, caller_confirmation: Address) {
+        access_control::renounce_role(&mut self.sdk, role, caller_confirmation);
+    }
+
+    fn set_role_admin(&mut self, role: B256, admin_role: B256) {
+        access_control::only_role(&mut self.sdk, DEFAULT_ADMIN_ROLE);
+        access_control::set_role_admin(&mut self.sdk, role, admin_role);
+    }
+
+    fn snapshot(&mut self) -> U256 {
+        access_control::only_role(&mut self.sdk, SNAPSHOT_ROLE);
+        snapshot::snapshot(&mut self.sdk)
+    }
+
+    // Takes `&mut self` only so an unknown id can revert with its error.
+    fn balance_of_at(&mut self, account: Address, snapshot_id: U256) -> U256 {
+        self.snapshot_balance(account, snapshot_id)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk))
+    }
+
+    fn total_supply_at(&mut self, snapshot_id: U256) -> U256 {
+        self.snapshot_total_supply(snapshot_id)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk))
+    }
+
+    fn clock(&self) -> u64 {
+        votes::clock(&self.sdk)
+    }
+
+    #[function_id("CLOCK_MODE()")]
+    fn clock_mode(&self) -> String {
+        String::from(votes::CLOCK_MODE)
+    }
+
+    fn delegates(&self, account: Address) -> Address {
+        votes::delegates(&self.sdk, account)
+    }
+
+    fn get_votes(&self, account: Address) -> U256 {
+        votes::get_votes(&self.sdk, account)
+    }
+
+    fn get_past_votes(&mut self, account: Address, timepoint: U256) -> U256 {
+        votes::get_past_votes(&self.sdk, account, timepoint)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk))
+    }
+
+    fn get_past_total_supply(&mut self, timepoint: U256) -> U256 {
+        votes::get_past_total_supply(&self.sdk, timepoint)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk))
+    }
+
+    fn delegate(&mut self, delegatee: Address) {
+        let account = self.sdk.context().contract_caller();
+        let units = self.units_of(account);
+        votes::delegate(&mut self.sdk, account, delegatee, units);
+    }
+
+    fn delegate_by_sig(
+        &mut self,
+        delegatee: Address,
+        nonce: U256,
+        expiry: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) {
+        let now = U256::from(self.sdk.context().block_timestamp());
+        if now > expiry {
+            TokenError::VotesExpiredSignature(expiry).revert(&mut self.sdk);
+        }
+
+        let digest = Delegation {
+            delegatee,
+            nonce,
+            expiry,
+        }
+        .eip712_signing_hash(&self.eip712_domain());
+        let signer =
+            eip712::recover::<SDK>(digest, v, r, s).unwrap_or_else(|err| err.revert(&mut self.sdk));
+        self.use_checked_nonce(signer, nonce)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+
+        let units = self.units_of(signer);
+        votes::delegate(&mut self.sdk, signer, delegatee, units);
+    }
+
+    /// Zero in rebasing mode, where minting and burning the same amount need
+    /// not move the same number of shares.
+    fn max_flash_loan(&self, token: Address) -> U256 {
+        if token != self.sdk.context().contract_address() || shares::rebasing(&self.sdk) {
+            return U256::ZERO;
+        }
+        Cap::get(&self.sdk) - TotalSupply::get(&self.sdk)
+    }
+
+    fn flash_fee(&mut self, token: Address, amount: U256) -> U256 {
+        if token != self.sdk.context().contract_address() {
+            TokenError::UnsupportedFlashToken(token).revert(&mut self.sdk);
+        }
+        flash_mint::flash_fee(amount)
+    }
+
+    fn flash_loan(&mut self, receiver: Address, token: Address, amount: U256, data: Bytes) -> bool {
+        self.execute_flash_loan(receiver, token, amount, data)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+        true
+    }
+
+    fn transfer_and_call(&mut self, to: Address, value: U256) -> bool {
+        self.transfer_and_call_with_data(to, value, Bytes::new())
+    }
+
+    #[function_id("transferAndCall(address,uint256,bytes)")]
+    fn transfer_and_call_with_data(&mut self, to: Address, value: U256, data: Bytes) -> bool {
+        let from = self.sdk.context().contract_caller();
+        let received = value - fees::fee_for(&self.sdk, from, to, value);
+        self.transfer_tokens(from, to, value)
+            .and_then(|()| self.check_on_transfer_received(from, from, to, received, data))
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+        true
+    }
+
+    fn transfer_from_and_call(&mut self, from: Address, to: Address, value: U256) -> bool {
+        self.transfer_from_and_call_with_data(from, to, value, Bytes::new())
+    }
+
+    #[function_id("transferFromAndCall(address,address,uint256,bytes)")]
+    fn transfer_from_and_call_with_data(
+        &mut self,
+        from: Address,
+        to: Address,
+        value: U256,
+        data: Bytes,
+    ) -> bool {
+        let spender = self.sdk.context().contract_caller();
+        let received = value - fees::fee_for(&self.sdk, from, to, value);
+        compliance::check(&self.sdk, spender)
+            .and_then(|()| self.spend_allowance(from, spender, value))
+            .and_then(|()| self.transfer_tokens(from, to, value))
+            .and_then(|()| self.check_on_transfer_received(spender, from, to, received, data))
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+        true
+    }
+
+    fn approve_and_call(&mut self, spender: Address, value: U256) -> bool {
+        self.approve_and_call_with_data(spender, value, Bytes::new())
+    }
+
+    #[function_id("approveAndCall(address,uint256,bytes)")]
+    fn approve_and_call_with_data(&mut self, spender: Address, value: U256, data: Bytes) -> bool {
+        let owner = self.sdk.context().contract_caller();
+        self.approve_tokens(owner, spender, value)
+            .and_then(|()| self.check_on_approval_received(owner, spender, value, data))
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+        true
+    }
+
+    fn supports_interface(&self, interface_id: FixedBytes<4>) -> bool {
+        interfaces::supports_interface(interface_id.0)
+    }
+
+    fn batch_transfer(&mut self, recipients: Vec<Address>, values: Vec<U256>) -> bool {
+        if recipients.len() != values.len() {
+            TokenError::BatchLengthMismatch {
+                recipients: recipients.len(),
+                values: values.len(),
+            }
+            .revert(&mut self.sdk);
+        }
+
+        let from = self.sdk.context().contract_caller();
+        for (to, value) in recipients.into_iter().zip(values) {
+            self.transfer_tokens(from, to, value)
+                .unwrap_or_else(|err| err.revert(&mut self.sdk));
+        }
+        true
+    }
+
+    /// The first failing payload reverts the whole batch with its data.
+    fn multicall(&mut self, data: Vec<Bytes>) -> Vec<Bytes> {
+        let mut results = Vec::with_capacity(data.len());
+        for payload in data {
+            match delegate_call_self(&mut self.sdk, &payload) {
+                Ok(output) => results.push(output),
+                Err(revert_data) => revert_with_data(&mut self.sdk, &revert_data),
+            }
+        }
+        results
+    }
+
+    fn transfer_fee_basis_points(&self) -> U256 {
+        fees::basis_points(&self.sdk)
+    }
+
+    fn fee_treasury(&self) -> Address {
+        fees::treasury(&self.sdk)
+    }
+
+    fn is_fee_exempt(&self, account: Address) -> bool {
+        fees::is_exempt(&self.sdk, account)
+    }
+
+    fn set_transfer_fee(&mut self, basis_points: U256, treasury: Address) {
+        access_control::only_role(&mut self.sdk, DEFAULT_ADMIN_ROLE);
+        fees::set_fee(&mut self.sdk, basis_points, treasury)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+    }
+
+    fn set_fee_exempt(&mut self, account: Address, exempt: bool) {
+        access_control::only_role(&mut self.sdk, DEFAULT_ADMIN_ROLE);
+        fees::set_exempt(&mut self.sdk, account, exempt);
+    }
+
+    fn is_blocked(&self, account: Address) -> bool {
+        compliance::is_blocked(&self.sdk, account)
+    }
+
+    fn is_allowlisted(&self, account: Address) -> bool {
+        compliance::is_allowlisted(&self.sdk, account)
+    }
+
+    fn allowlist_only(&self) -> bool {
+        compliance::allowlist_only(&self.sdk)
+    }
+
+    fn set_blocked(&mut self, account: Address, blocked: bool) {
+        access_control::only_role(&mut self.sdk, COMPLIANCE_ROLE);
+        compliance::set_blocked(&mut self.sdk, account, blocked);
+    }
+
+    fn set_allowlisted(&mut self, account: Address, allowed: bool) {
+        access_control::only_role(&mut self.sdk, COMPLIANCE_ROLE);
+        compliance::set_allowlisted(&mut self.sdk, account, allowed);
+    }
+
+    fn set_allowlist_only(&mut self, enabled: bool) {
+        access_control::only_role(&mut self.sdk, COMPLIANCE_ROLE);
+        compliance::set_allowlist_only(&mut self.sdk, enabled);
+    }
+
+    fn seize(&mut self, from: Address, to: Address, amount: U256) {
+        access_control::only_role(&mut self.sdk, COMPLIANCE_ROLE);
+        self.seize_tokens(from, to, amount)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+    }
+
+    /// Funded by the caller, without a transfer fee.
+    fn create_vesting_schedule(
+        &mut self,
+        beneficiary: Address,
+        start: u64,
+        cliff: u64,
+        duration: u64,
+        amount: U256,
+    ) {
+        access_control::only_role(&mut self.sdk, DEFAULT_ADMIN_ROLE);
+        if beneficiary == Address::ZERO {
+            TokenError::InvalidReceiver(Address::ZERO).revert(&mut self.sdk);
+        }
+        let owner = self.sdk.context().contract_caller();
+        compliance::check(&self.sdk, beneficiary)
+            .and_then(|()| self.units_for(amount))
+            .and_then(|units| {
+                vesting::create_schedule(&mut self.sdk, beneficiary, start, cliff, duration, units)
+            })
+            .and_then(|()| self.update(owner, beneficiary, amount))
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
+    }
+
+    fn vested_amount(&mut self, beneficiary: Address, timestamp: u64) -> U256 {
+        let units = vesting::vested_amount(&self.sdk, beneficiary, timestamp);
+        self.tokens_for_or_revert(units)
+    }
+
+    fn released(&mut self, beneficiary: Address) -> U256 {
+        let units = vesting::released(&self.sdk, beneficiary);
+        self.tokens_for_or_revert(units)
+    }
+
+    fn releasable(&mut self, beneficiary: Address) -> U256 {
+        let units = vesting::releasable(&self.sdk, beneficiary);
+        self.tokens_for_or_revert(units)
+    }
+
+    fn locked_balance_of(&mut self, account: Address) -> U256 {
+        let units = vesting::locked(&self.sdk, account);
+        self.tokens_for_or_revert(units)
+    }
+
+    fn release(&mut self) -> U256 {
+        let beneficiary = self.sdk.context().contract_caller();
+        let units = vesting::release(&mut self.sdk, beneficiary);
+        self.tokens_for_or_revert(units)
+    }
+
+    fn rebasing(&self) -> bool {
+        shares::rebasing(&self.sdk)
+    }
+
+    fn shares_of(&self, account: Address) -> U256 {
+        shares::shares_of(&self.sdk, account)
+    }
+
+    fn total_shares(&self) -> U256 {
+        shares::total_shares(&self.sdk)
+    }
+
+    fn get_shares_by_pooled_token(&mut self, amount: U256) -> U256 {
+        let total_pooled = TotalSupply::get(&self.sdk);
+        shares::shares_by_pooled_token(&self.sdk, amount, total_pooled)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk))
+    }
+
+    fn get_pooled_token_by_shares(&mut self, shares: U256) -> U256 {
+        self.tokens_for_or_revert(shares)
+    }
+
+    fn rebase(&mut self, new_total: U256) {
+        access_control::only_role(&mut self.sdk, ORACLE_ROLE);
+        if !shares::rebasing(&self.sdk) {
+            TokenError::RebasingDisabled.revert(&mut self.sdk);
+        }
+        let total_shares = shares::total_shares(&self.sdk);
+        if total_shares.is_zero() || new_total.is_zero() {
+            TokenError::InvalidRebase {
+                total_shares,
+                new_total,
+            }
+            .revert(&mut self.sdk);
+        }
+        let cap = Cap::get(&self.sdk);
+        if new_total > cap {
+            TokenError::ExceededCap {
+                increased_supply: new_total,
+                cap,
+            }
+            .revert(&mut self.sdk);
+        }
+        let previous_total = TotalSupply::get(&self.sdk);
+        snapshot::update_pooled_total(&mut self.sdk, previous_total);
+        TotalSupply::set(&mut self.sdk, new_total);
+        shares::rebased(&mut self.sdk, previous_total, new_total);
     }
 }
 
 impl<SDK: SharedAPI> ERC20<SDK> {
     pub fn deploy(&mut self) {
-        let owner_address = self.sdk.context().contract_caller();
-        let owner_balance: U256 = U256::from_str_radix("1000000000000000000000000", 10).unwrap();
+        let input = self.sdk.input();
+        let args = <ConstructorArgs as SolType>::abi_decode_params(&input)
+            .unwrap_or_else(|_| TokenError::InvalidConstructorArgs.revert(&mut self.sdk));
+
+        Name::set(&mut self.sdk, Bytes::from(args.name.into_bytes()));
+        Symbol::set(&mut self.sdk, Bytes::from(args.symbol.into_bytes()));
+        Decimals::set(&mut self.sdk, U256::from(args.decimals));
+        if args.cap.is_zero() {
+            TokenError::InvalidCap(args.cap).revert(&mut self.sdk);
+        }
+        Cap::set(&mut self.sdk, args.cap);
+        shares::initialize(&mut self.sdk, args.rebasing);
+        ownable::initialize(&mut self.sdk, args.initialOwner);
+        for role in OWNER_ROLES {
+            access_control::grant_role_unchecked(&mut self.sdk, role, args.initialOwner);
+        }
 
-        let _ = Balance::add(&mut self.sdk, owner_address, owner_balance);
+        self.mint_tokens(args.initialOwner, args.initialSupply)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk));
     }
-}
 
-basic_entrypoint!(ERC20);
+    /// Moves the `OWNER_ROLES` that `from` still holds to `to`, or drops them
+    /// when ownership is renounced.
+    fn hand_over_roles(&mut self, from: Address, to: Address) {
+        for role in OWNER_ROLES {
+            if access_control::revoke_role_unchecked(&mut self.sdk, role, from)
+                && to != Address::ZERO
+            {
+                access_control::grant_role_unchecked(&mut self.sdk, role, to);
+            }
+        }
+    }
+
+    fn eip712_domain(&self) -> Eip712Domain {
+        eip712::domain(&self.sdk, self.name())
+    }
+
+    fn use_nonce(&mut self, owner: Address) -> U256 {
+        let nonce = Nonces::get(&self.sdk, owner);
+        Nonces::set(&mut self.sdk, owner, nonce + U256::from(1));
+        nonce
+    }
+
+    fn use_checked_nonce(&mut self, owner: Address, nonce: U256) -> Result<(), TokenError> {
+        let current = self.use_nonce(owner);
+        if current != nonce {
+            return Err(TokenError::InvalidAccountNonce {
+                account: owner,
+                current_nonce: current,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_on_transfer_received(
+        &mut self,
+        operator: Address,
+        from: Address,
+        to: Address,
+        value: U256,
+        data: Bytes,
+    ) -> Result<(), TokenError> {
+        let input = erc1363::on_transfer_received_input(operator, from, value, data);
+        match call_contract(&mut self.sdk, to, &input) {
+            Some(output) if erc1363::is_acknowledged(&output, erc1363::ON_TRANSFER_RECEIVED) => {
+                Ok(())
+            }
+            _ => Err(TokenError::InvalidERC1363Receiver(to)),
+        }
+    }
+
+    fn check_on_approval_received(
+        &mut self,
+        owner: Address,
+        spender: Address,
+        value: U256,
+        data: Bytes,
+    ) -> Result<(), TokenError> {
+        let input = erc1363::on_approval_received_input(owner, value, data);
+        match call_contract(&mut self.sdk, spender, &input) {
+            Some(output) if erc1363::is_acknowledged(&output, erc1363::ON_APPROVAL_RECEIVED) => {
+                Ok(())
+            }
+            _ => Err(TokenError::InvalidERC1363Spender(spender)),
+        }
+    }
+
+    fn execute_flash_loan(
+        &mut self,
+        receiver: Address,
+        token: Address,
+        amount: U256,
+        data: Bytes,
+    ) -> Result<(), TokenError> {
+        let max_loan = self.max_flash_loan(token);
+        if amount > max_loan {
+            return Err(TokenError::ExceededMaxLoan(max_loan));
+        }
+        let fee = self.flash_fee(token, amount);
+
+        self.mint_tokens(receiver, amount)?;
+
+        let initiator = self.sdk.context().contract_caller();
+        let input = flash_mint::on_flash_loan_input(initiator, token, amount, fee, data);
+        match call_contract(&mut self.sdk, receiver, &input) {
+            Some(output) if flash_mint::is_callback_success(&output) => {}
+            _ => return Err(TokenError::InvalidFlashReceiver(receiver)),
+        }
+
+        // The borrower repays by approving this contract for loan plus fee.
+        let token_address = self.sdk.context().contract_address();
+        let repayment = amount
+            .checked_add(fee)
+            .ok_or(TokenError::ArithmeticOverflow)?;
+        self.spend_allowance(receiver, token_address, repayment)?;
+        self.burn_tokens(receiver, repayment)
+    }
+
+    /// `to` receives `value` minus the transfer fee, if any.
+    fn transfer_tokens(
+        &mut self,
+        from: Address,
+        to: Address,
+        value: U256,
+    ) -> Result<(), TokenError> {
+        if from == Address::ZERO {
+            return Err(TokenError::InvalidSender(Address::ZERO));
+        }
+        if to == Address::ZERO {
+            return Err(TokenError::InvalidReceiver(Address::ZERO));
+        }
+        compliance::check(&self.sdk, from)?;
+        compliance::check(&self.sdk, to)?;
+        let fee = fees::fee_for(&self.sdk, from, to, value);
+        if !fee.is_zero() {
+            let balance = self.balance(from)?;
+            if balance < value {
+                return Err(TokenError::InsufficientBalance {
+                    sender: from,
+                    balance,
+                    needed: value,
+                });
+            }
+            let treasury = fees::treasury(&self.sdk);
+            compliance::check(&self.sdk, treasury)?;
+            self.update(from, treasury, fee)?;
+        }
+        self.update(from, to, value - fee)
+    }
+
+    fn seize_tokens(&mut self, from: Address, to: Address, value: U256) -> Result<(), TokenError> {
+        if !compliance::is_blocked(&self.sdk, from) {
+            return Err(TokenError::AccountNotBlocked(from));
+        }
+        if to == Address::ZERO {
+            return Err(TokenError::InvalidReceiver(Address::ZERO));
+        }
+        // Unlocked tokens go first; whatever is taken beyond them ends that
+        // part of the vesting schedule.
+        let unlocked = self
+            .units_of(from)
+            .saturating_sub(vesting::locked(&self.sdk, from));
+        let taken_locked = self.units_for(value)?.saturating_sub(unlocked);
+        self.move_balance(from, to, value)?;
+        if !taken_locked.is_zero() {
+            vesting::forfeit(&mut self.sdk, from, taken_locked);
+        }
+        Ok(())
+    }
+
+    fn mint_tokens(&mut self, to: Address, value: U256) -> Result<(), TokenError> {
+        if to == Address::ZERO {
+            return Err(TokenError::InvalidReceiver(Address::ZERO));
+        }
+        compliance::check(&self.sdk, to)?;
+        self.update(Address::ZERO, to, value)
+    }
+
+    fn burn_tokens(&mut self, from: Address, value: U256) -> Result<(), TokenError> {
+        if from == Address::ZERO {
+            return Err(TokenError::InvalidSender(Address::ZERO));
+        }
+        self.update(from, Address::ZERO, value)
+    }
+
+    /// Like OpenZeppelin's `_update`: a zero `from` mints, a zero `to` burns.
+    fn update(&mut self, from: Address, to: Address, value: U256) -> Result<(), TokenError> {
+        if pausable::paused(&self.sdk) {
+            return Err(TokenError::EnforcedPause);
+        }
+        if from != Address::ZERO {
+            let locked = vesting::locked(&self.sdk, from);
+            if !locked.is_zero() {
+                let unlocked = self.units_of(from).saturating_sub(locked);
+                if unlocked < self.units_for(value)? {
+                    return Err(TokenError::LockedBalance {
+                        account: from,
+                        unlocked: self.tokens_for(unlocked)?,
+                        needed: value,
+                    });
+                }
+            }
+        }
+        self.move_balance(from, to, value)
+    }
+
+    /// `update` without the pause and vesting checks, so `seize` always works.
+    fn move_balance(&mut self, from: Address, to: Address, value: U256) -> Result<(), TokenError> {
+        self.update_snapshots(from, to);
+
+        let rebasing = shares::rebasing(&self.sdk);
+        let moved_shares = if from == Address::ZERO {
+            self.units_issued_for(value)?
+        } else {
+            self.units_for(value)?
+        };
+
+        if from == Address::ZERO {
+            let supply = TotalSupply::get(&self.sdk)
+                .checked_add(value)
+                .ok_or(TokenError::ArithmeticOverflow)?;
+            let cap = Cap::get(&self.sdk);
+            if supply > cap {
+                return Err(TokenError::ExceededCap {
+                    increased_supply: supply,
+                    cap,
+                });
+            }
+            TotalSupply::set(&mut self.sdk, supply);
+        } else if rebasing {
+            let balance = self.balance(from)?;
+            if balance < value {
+                return Err(TokenError::InsufficientBalance {
+                    sender: from,
+                    balance,
+                    needed: value,
+                });
+            }
+        } else {
+            Balance::subtract(&mut self.sdk, from, value)?;
+        }
+
+        if to == Address::ZERO {
+            let supply = TotalSupply::get(&self.sdk);
+            TotalSupply::set(&mut self.sdk, supply - value);
+        } else if !rebasing {
+            Balance::add(&mut self.sdk, to, value)?;
+        }
+
+        if rebasing {
+            shares::move_shares(&mut self.sdk, from, to, moved_shares)?;
+        }
+        votes::transfer_voting_units(&mut self.sdk, from, to, moved_shares);
+
+        emit_event(&mut self.sdk, Transfer { from, to, value });
+        if rebasing {
+            emit_event(
+                &mut self.sdk,
+                shares::TransferShares {
+                    from,
+                    to,
+                    sharesValue: moved_shares,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn balance(&self, account: Address) -> Result<U256, TokenError> {
+        self.tokens_for(self.units_of(account))
+    }
+
+    /// Shares in rebasing mode, tokens otherwise.
+    fn units_of(&self, account: Address) -> U256 {
+        if shares::rebasing(&self.sdk) {
+            shares::shares_of(&self.sdk, account)
+        } else {
+            Balance::get(&self.sdk, account)
+        }
+    }
+
+    /// The units an account gives up to send or burn `value` tokens, rounded
+    /// up so that no non-zero amount is free.
+    fn units_for(&self, value: U256) -> Result<U256, TokenError> {
+        if !shares::rebasing(&self.sdk) {
+            return Ok(value);
+        }
+        let total_pooled = TotalSupply::get(&self.sdk);
+        shares::shares_by_pooled_token_up(&self.sdk, value, total_pooled)
+    }
+
+    /// The units minted for `value` new tokens, rounded down.
+    fn units_issued_for(&self, value: U256) -> Result<U256, TokenError> {
+        if !shares::rebasing(&self.sdk) {
+            return Ok(value);
+        }
+        let total_pooled = TotalSupply::get(&self.sdk);
+        shares::shares_by_pooled_token(&self.sdk, value, total_pooled)
+    }
+
+    fn tokens_for(&self, units: U256) -> Result<U256, TokenError> {
+        if !shares::rebasing(&self.sdk) {
+            return Ok(units);
+        }
+        let total_pooled = TotalSupply::get(&self.sdk);
+        shares::pooled_token_by_shares(&self.sdk, units, total_pooled)
+    }
+
+    fn tokens_for_or_revert(&mut self, units: U256) -> U256 {
+        self.tokens_for(units)
+            .unwrap_or_else(|err| err.revert(&mut self.sdk))
+    }
+
+    fn total_units(&self) -> U256 {
+        if shares::rebasing(&self.sdk) {
+            shares::total_shares(&self.sdk)
+        } else {
+            TotalSupply::get(&self.sdk)
+        }
+    }
+
+    /// In tokens; rebasing mode converts the recorded shares with the
+    /// snapshot's own totals.
+    fn snapshot_balance(&self, account: Address, id: U256) -> Result<U256, TokenError> {
+        let units =
+            snapshot::balance_at(&self.sdk, account, id)?.unwrap_or_else(|| self.units_of(account));
+        if !shares::rebasing(&self.sdk) {
+            return Ok(units);
+        }
+        let total_shares = snapshot::total_supply_at(&self.sdk, id)?
+            .unwrap_or_else(|| shares::total_shares(&self.sdk));
+        let total_pooled = self.snapshot_total_supply(id)?;
+        shares::pooled_token_by_shares_at(units, total_shares, total_pooled)
+    }
+
+    fn snapshot_total_supply(&self, id: U256) -> Result<U256, TokenError> {
+        let recorded = if shares::rebasing(&self.sdk) {
+            snapshot::pooled_total_at(&self.sdk, id)?
+        } else {
+            snapshot::total_supply_at(&self.sdk, id)?
+        };
+        Ok(recorded.unwrap_or_else(|| TotalSupply::get(&self.sdk)))
+    }
+
+    /// A `U256::MAX` allowance is never decreased.
+    fn spend_allowance(
+        &mut self,
+        owner: Address,
+        spender: Address,
+        value: U256,
+    ) -> Result<(), TokenError> {
+        let current_allowance = Allowance::get(&self.sdk, owner, spender);
+        if current_allowance == U256::MAX {
+            return Ok(());
+        }
+        if current_allowance < value {
+            return Err(TokenError::InsufficientAllowance {
+                spender,
+                allowance: current_allowance,
+                needed: value,
+            });
+        }
+        Allowance::set(&mut self.sdk, owner, spender, current_allowance - value);
+        Ok(())
+    }
+
+    fn update_snapshots(&mut self, from: Address, to: Address) {
+        for account in [from, to] {
+            if account == Address::ZERO {
+                let supply = self.total_units();
+                snapshot::update_total_supply(&mut self.sdk, supply);
+                if shares::rebasing(&self.sdk) {
+                    let total_pooled = TotalSupply::get(&self.sdk);
+                    snapshot::update_pooled_total(&mut self.sdk, total_pooled);
+                }
+            } else {
+                let balance = self.units_of(account);
+                snapshot::update_account(&mut self.sdk, account, balance);
+            }
+        }
+    }
+
+    fn approve_tokens(
+        &mut self,
+        owner: Address,
+        spender: Address,
+        value: U256,
+    ) -> Result<(), TokenError> {
+        if owner == Address::ZERO {
+            return Err(TokenError::InvalidApprover(Address::ZERO));
+        }
+        if spender == Address::ZERO {
+            return Err(TokenError::InvalidSpender(Address::ZERO));
+        }
+        // Revoking is always allowed.
+        if !value.is_zero() {
+            compliance::check(&self.sdk, owner)?;
+            compliance::check(&self.sdk, spender)?;
+        }
+        Allowance::set(&mut self.sdk, owner, spender, value);
+
+        emit_event(
+            &mut self.sdk,
+            Approval {
+                owner,
+                spender,
+                value,
+            },
+        );
+        Ok(())
+    }
+
+    fn adjust_allowance(
+        &mut self,
+        owner: Address,
+        spender: Address,
+        delta: U256,
+        increase: bool,
+    ) -> Result<(), TokenError> {
+        if spender == Address::ZERO {
+            return Err(TokenError::InvalidSpender(Address::ZERO));
+        }
+        if increase {
+            compliance::check(&self.sdk, owner)?;
+            compliance::check(&self.sdk, spender)?;
+            Allowance::add(&mut self.sdk, owner, spender, delta)?;
+        } else {
+            Allowance::subtract(&mut self.sdk, owner, spender, delta).map_err(|err| match err {
+                TokenError::InsufficientAllowance {
+                    spender,
+                    allowance,
+                    needed,
+                } => TokenError::FailedDecreaseAllowance {
+                    spender,
+                    current_allowance: allowance,
+                    requested_decrease: needed,
+                },
+                err => err,
+            })?;
+        }
+
+        let value = Allowance::get(&self.sdk, owner, spender);
+        emit_event(
+            &mut self.sdk,
+            Approval {
+                owner,
+                spender,
+                value,
+            },
+        );
+        Ok(())
+    }
+}
+
+basic_entrypoint!(ERC20);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_sol_types::{Panic, SolCall};
+    use fluentbase_sdk::{BlockContextV1, ContractContextV1};
+    use fluentbase_sdk_testing::HostTestingContext;
+    use std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+        rc::Rc,
+    };
+
+    const TOKEN: Address = Address::repeat_byte(0x70);
+    const OWNER: Address = Address::repeat_byte(0x01);
+    const ALICE: Address = Address::repeat_byte(0x0a);
+    const BOB: Address = Address::repeat_byte(0x0b);
+    const CHAIN_ID: u64 = 1;
+
+    fn constructor_args() -> ConstructorArgs {
+        ConstructorArgs {
+            name: "RustyToken".into(),
+            symbol: "RUST".into(),
+            decimals: 18,
+            initialSupply: U256::from(1_000_000),
+            initialOwner: OWNER,
+            cap: U256::from(10_000_000),
+            rebasing: false,
+        }
+    }
+
+    fn deploy(args: ConstructorArgs) -> (HostTestingContext, ERC20<HostTestingContext>) {
+        let sdk = HostTestingContext::default()
+            .with_input(<ConstructorArgs as SolType>::abi_encode_params(&args));
+        set_block(&sdk, 1, 1_000);
+        set_caller(&sdk, OWNER);
+        MOCKS.with(|mocks| mocks.borrow_mut().clear());
+        let mut token = ERC20::new(sdk.clone());
+        token.deploy();
+        (sdk, token)
+    }
+
+    type MockCall = Box<dyn FnMut(&[u8]) -> Result<Bytes, Bytes>>;
+
+    thread_local! {
+        static MOCKS: RefCell<HashMap<Address, MockCall>> = RefCell::new(HashMap::new());
+    }
+
+    /// Calls to `target` run `call` instead; an `Err` is the revert data.
+    fn mock_contract(target: Address, call: impl FnMut(&[u8]) -> Result<Bytes, Bytes> + 'static) {
+        MOCKS.with(|mocks| mocks.borrow_mut().insert(target, Box::new(call)));
+    }
+
+    /// Unmocked targets behave like EOAs: the call succeeds with no output.
+    fn run_mock(target: Address, input: &[u8]) -> Result<Bytes, Bytes> {
+        // Taken out while it runs, so it can call other mocks.
+        let Some(mut call) = MOCKS.with(|mocks| mocks.borrow_mut().remove(&target)) else {
+            return Ok(Bytes::new());
+        };
+        let result = call(input);
+        MOCKS.with(|mocks| mocks.borrow_mut().insert(target, call));
+        result
+    }
+
+    pub(super) fn call_contract<SDK: SharedAPI>(
+        _sdk: &mut SDK,
+        target: Address,
+        input: &[u8],
+    ) -> Option<Bytes> {
+        run_mock(target, input).ok()
+    }
+
+    pub(super) fn delegate_call_self<SDK: SharedAPI>(
+        sdk: &mut SDK,
+        input: &[u8],
+    ) -> Result<Bytes, Bytes> {
+        let target = sdk.context().contract_address();
+        run_mock(target, input)
+    }
+
+    /// The context is shared between clones, so this also switches the
+    /// sender seen by the token.
+    fn set_caller(sdk: &HostTestingContext, caller: Address) {
+        let _ = sdk.clone().with_contract_context(ContractContextV1 {
+            address: TOKEN,
+            caller,
+            ..Default::default()
+        });
+    }
+
+    fn set_block(sdk: &HostTestingContext, number: u64, timestamp: u64) {
+        let _ = sdk.clone().with_block_context(BlockContextV1 {
+            chain_id: CHAIN_ID,
+            number,
+            timestamp,
+            ..Default::default()
+        });
+    }
+
+    /// Runs `call`, which must revert, and returns its revert data.
+    fn revert_data(sdk: &HostTestingContext, call: impl FnOnce()) -> Vec<u8> {
+        let _ = sdk.take_output();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(call));
+        assert!(result.is_err(), "call did not revert");
+        sdk.take_output()
+    }
+
+    fn sum_of_balances(token: &ERC20<HostTestingContext>, accounts: &[Address]) -> U256 {
+        accounts.iter().fold(U256::ZERO, |sum, account| {
+            sum + Balance::get(&token.sdk, *account)
+        })
+    }
+
+    #[test]
+    fn balances_sum_to_total_supply() {
+        let (sdk, mut token) = deploy(constructor_args());
+        let accounts = [OWNER, ALICE, BOB];
+        assert_eq!(sum_of_balances(&token, &accounts), token.total_supply());
+
+        token.transfer(ALICE, U256::from(300));
+        assert_eq!(sum_of_balances(&token, &accounts), token.total_supply());
+
+        token.mint(BOB, U256::from(500));
+        assert_eq!(sum_of_balances(&token, &accounts), token.total_supply());
+
+        set_caller(&sdk, ALICE);
+        token.burn(U256::from(100));
+        assert_eq!(sum_of_balances(&token, &accounts), token.total_supply());
+
+        set_caller(&sdk, BOB);
+        token.transfer(ALICE, U256::from(50));
+        assert_eq!(sum_of_balances(&token, &accounts), token.total_supply());
+        assert_eq!(token.total_supply(), U256::from(1_000_400));
+    }
+
+    #[test]
+    fn deploy_rejects_malformed_arguments() {
+        let sdk = HostTestingContext::default().with_input(vec![0xde, 0xad]);
+        set_caller(&sdk, OWNER);
+        let mut token = ERC20::new(sdk.clone());
+        assert_eq!(
+            revert_data(&sdk, || token.deploy()),
+            TokenError::InvalidConstructorArgs.abi_encode()
+        );
+    }
+
+    /// Every module declares its own `solidity_storage!` block; writing
+    /// through all of them must leave the token's own slots untouched.
+    #[test]
+    fn module_storage_is_disjoint() {
+        let (_sdk, mut token) = deploy(constructor_args());
+        token.approve(ALICE, U256::from(77));
+
+        token.transfer_ownership(BOB);
+        token.grant_role(MINTER_ROLE, ALICE);
+        token.snapshot();
+        token.delegate(OWNER);
+        token.set_transfer_fee(U256::from(100), BOB);
+        token.set_fee_exempt(ALICE, true);
+        token.set_blocked(BOB, true);
+        token.set_allowlisted(ALICE, true);
+        vesting::create_schedule(&mut token.sdk, ALICE, 0, 0, 10, U256::from(5)).unwrap();
+        shares::move_shares(&mut token.sdk, Address::ZERO, ALICE, U256::from(9)).unwrap();
+        token.pause();
+
+        assert_eq!(Balance::get(&token.sdk, OWNER), U256::from(1_000_000));
+        assert_eq!(Balance::get(&token.sdk, ALICE), U256::ZERO);
+        assert_eq!(Balance::get(&token.sdk, BOB), U256::ZERO);
+        assert_eq!(Allowance::get(&token.sdk, OWNER, ALICE), U256::from(77));
+        assert_eq!(TotalSupply::get(&token.sdk), U256::from(1_000_000));
+        assert_eq!(Cap::get(&token.sdk), U256::from(10_000_000));
+        assert_eq!(token.name(), "RustyToken");
+
+        assert_eq!(token.owner(), OWNER);
+        assert_eq!(token.pending_owner(), BOB);
+        assert!(token.has_role(MINTER_ROLE, ALICE));
+        assert_eq!(snapshot::current_id(&token.sdk), U256::from(1));
+        assert_eq!(token.get_votes(OWNER), U256::from(1_000_000));
+        assert_eq!(token.transfer_fee_basis_points(), U256::from(100));
+        assert!(token.is_fee_exempt(ALICE));
+        assert!(token.is_blocked(BOB));
+        assert!(token.is_allowlisted(ALICE));
+        assert_eq!(vesting::locked(&token.sdk, ALICE), U256::from(5));
+        assert_eq!(token.shares_of(ALICE), U256::from(9));
+        assert!(token.paused());
+    }
+
+    fn overflow_panic() -> Vec<u8> {
+        let data = Panic {
+            code: U256::from(0x11),
+        }
+        .abi_encode();
+        assert_eq!(data[..4], hex!("4e487b71"));
+        data
+    }
+
+    #[test]
+    fn balance_and_allowance_credits_do_not_wrap() {
+        let (sdk, mut token) = deploy(constructor_args());
+
+        Balance::set(&mut token.sdk, ALICE, U256::MAX);
+        assert_eq!(
+            Balance::add(&mut token.sdk, ALICE, U256::from(1)),
+            Err(TokenError::ArithmeticOverflow)
+        );
+        assert_eq!(Balance::get(&token.sdk, ALICE), U256::MAX);
+
+        token.approve(ALICE, U256::MAX - U256::from(1));
+        token.increase_allowance(ALICE, U256::from(1));
+        assert_eq!(token.allowance(OWNER, ALICE), U256::MAX);
+        let data = revert_data(&sdk, || {
+            token.increase_allowance(ALICE, U256::from(1));
+        });
+        assert_eq!(data, overflow_panic());
+        assert_eq!(token.allowance(OWNER, ALICE), U256::MAX);
+    }
+
+    #[test]
+    fn mint_past_u256_max_panics() {
+        let (sdk, mut token) = deploy(ConstructorArgs {
+            initialSupply: U256::MAX,
+            cap: U256::MAX,
+            ..constructor_args()
+        });
+        let data = revert_data(&sdk, || token.mint(BOB, U256::from(1)));
+        assert_eq!(data, overflow_panic());
+        assert_eq!(token.total_supply(), U256::MAX);
+        assert_eq!(token.balance_of(BOB), U256::ZERO);
+    }
+
+    // Reference permit signed off-chain with Hardhat's first dev key for a
+    // `RustyToken` at `TOKEN` on chain 1, as ethers and OpenZeppelin encode it.
+    const PERMIT_SIGNER: Address = Address::new(hex!("f39fd6e51aad88f6f4ce6ab8827279cfffb92266"));
+    const PERMIT_DOMAIN_SEPARATOR: B256 = B256::new(hex!(
+        "12897f651c5b39d950a773e3b0033419cc98b32af0d012983e4b6e16a4fdcc32"
+    ));
+    const PERMIT_DIGEST: B256 = B256::new(hex!(
+        "0f6e50d349d4a22061129786c648abf43eeb31e6d905edf5a737adccb9a987b4"
+    ));
+    const PERMIT_R: B256 = B256::new(hex!(
+        "b7973ff0bd88aff8d5572f4f7de21f882add8c6655aea526bf5f6276dfa620c2"
+    ));
+    const PERMIT_S: B256 = B256::new(hex!(
+        "10e0fff1a57bc8bf0cb7d3bc21db587d05eb44e01f03cb22e2bdf847368d50b9"
+    ));
+    // secp256k1n - PERMIT_S: the same signature in its malleable form.
+    const PERMIT_HIGH_S: B256 = B256::new(hex!(
+        "ef1f000e5a843740f3482c43de24a781b4c398069044d518dd14664599a8f088"
+    ));
+
+    #[test]
+    fn permit_matches_reference_vector() {
+        let (sdk, mut token) = deploy(constructor_args());
+        assert_eq!(token.domain_separator(), PERMIT_DOMAIN_SEPARATOR);
+
+        let (value, deadline) = (U256::from(1_000), U256::from(2_000));
+        let digest = Permit {
+            owner: PERMIT_SIGNER,
+            spender: ALICE,
+            value,
+            nonce: U256::ZERO,
+            deadline,
+        }
+        .eip712_signing_hash(&token.eip712_domain());
+        assert_eq!(digest, PERMIT_DIGEST);
+        assert_eq!(
+            eip712::recover::<HostTestingContext>(digest, 27, PERMIT_R, PERMIT_S),
+            Ok(PERMIT_SIGNER)
+        );
+        assert_eq!(
+            eip712::recover::<HostTestingContext>(digest, 27, PERMIT_R, PERMIT_HIGH_S),
+            Err(TokenError::InvalidSignatureS(PERMIT_HIGH_S))
+        );
+
+        set_caller(&sdk, BOB);
+        token.permit(
+            PERMIT_SIGNER,
+            ALICE,
+            value,
+            deadline,
+            27,
+            PERMIT_R,
+            PERMIT_S,
+        );
+        assert_eq!(token.allowance(PERMIT_SIGNER, ALICE), value);
+        assert_eq!(token.nonces(PERMIT_SIGNER), U256::from(1));
+
+        // The nonce moved on, so a replay recovers some other signer.
+        let data = revert_data(&sdk, || {
+            token.permit(
+                PERMIT_SIGNER,
+                ALICE,
+                value,
+                deadline,
+                27,
+                PERMIT_R,
+                PERMIT_S,
+            )
+        });
+        assert_eq!(data[..4], error::ERC2612InvalidSigner::SELECTOR);
+    }
+
+    #[test]
+    fn ownership_handover_moves_roles() {
+        let (sdk, mut token) = deploy(constructor_args());
+        token.transfer_ownership(ALICE);
+        set_caller(&sdk, ALICE);
+        token.accept_ownership();
+        for role in OWNER_ROLES {
+            assert!(token.has_role(role, ALICE));
+            assert!(!token.has_role(role, OWNER));
+        }
+        token.set_transfer_fee(U256::from(100), BOB);
+
+        set_caller(&sdk, OWNER);
+        let data = revert_data(&sdk, || token.mint(OWNER, U256::from(1)));
+        assert_eq!(
+            data,
+            access_control::AccessControlUnauthorizedAccount {
+                account: OWNER,
+                neededRole: MINTER_ROLE,
+            }
+            .abi_encode()
+        );
+
+        set_caller(&sdk, ALICE);
+        token.renounce_ownership();
+        for role in OWNER_ROLES {
+            assert!(!token.has_role(role, ALICE));
+        }
+    }
+
+    #[test]
+    fn role_admins_can_be_delegated() {
+        let (sdk, mut token) = deploy(constructor_args());
+        assert_eq!(token.get_role_admin(MINTER_ROLE), DEFAULT_ADMIN_ROLE);
+
+        token.set_role_admin(MINTER_ROLE, PAUSER_ROLE);
+        assert_eq!(token.get_role_admin(MINTER_ROLE), PAUSER_ROLE);
+        assert_eq!(token.get_role_admin(PAUSER_ROLE), DEFAULT_ADMIN_ROLE);
+        token.grant_role(PAUSER_ROLE, ALICE);
+
+        set_caller(&sdk, ALICE);
+        token.grant_role(MINTER_ROLE, BOB);
+        assert!(token.has_role(MINTER_ROLE, BOB));
+        let data = revert_data(&sdk, || token.set_role_admin(MINTER_ROLE, MINTER_ROLE));
+        assert_eq!(
+            data,
+            access_control::AccessControlUnauthorizedAccount {
+                account: ALICE,
+                neededRole: DEFAULT_ADMIN_ROLE,
+            }
+            .abi_encode()
+        );
+    }
+
+    #[test]
+    fn snapshots_keep_balances_across_later_transfers() {
+        let (sdk, mut token) = deploy(constructor_args());
+        token.transfer(ALICE, U256::from(100));
+        assert_eq!(token.snapshot(), U256::from(1));
+
+        set_caller(&sdk, ALICE);
+        token.transfer(BOB, U256::from(50));
+        set_caller(&sdk, OWNER);
+        assert_eq!(token.snapshot(), U256::from(2));
+        assert_eq!(token.snapshot(), U256::from(3));
+        token.mint(BOB, U256::from(1_000));
+        token.transfer(ALICE, U256::from(10));
+
+        let at = |token: &mut ERC20<HostTestingContext>, account, id: u64| {
+            token.balance_of_at(account, U256::from(id))
+        };
+        assert_eq!(at(&mut token, ALICE, 1), U256::from(100));
+        assert_eq!(at(&mut token, ALICE, 2), U256::from(50));
+        assert_eq!(at(&mut token, ALICE, 3), U256::from(50));
+        assert_eq!(token.balance_of(ALICE), U256::from(60));
+        assert_eq!(at(&mut token, BOB, 1), U256::ZERO);
+        assert_eq!(at(&mut token, BOB, 3), U256::from(50));
+        assert_eq!(token.balance_of(BOB), U256::from(1_050));
+        assert_eq!(at(&mut token, OWNER, 1), U256::from(999_900));
+        assert_eq!(at(&mut token, OWNER, 3), U256::from(999_900));
+        for id in 1..=3 {
+            assert_eq!(token.total_supply_at(U256::from(id)), U256::from(1_000_000));
+        }
+        assert_eq!(token.total_supply(), U256::from(1_001_000));
+
+        for id in [0, 4] {
+            let data = revert_data(&sdk, || {
+                token.balance_of_at(ALICE, U256::from(id));
+            });
+            assert_eq!(
+                data,
+                TokenError::InvalidSnapshotId(U256::from(id)).abi_encode()
+            );
+        }
+    }
+
+    #[test]
+    fn past_votes_follow_checkpoints() {
+        let (sdk, mut token) = deploy(constructor_args());
+        token.delegate(OWNER);
+        set_block(&sdk, 5, 1_060);
+        token.transfer(ALICE, U256::from(100));
+        set_block(&sdk, 10, 1_120);
+
+        assert_eq!(
+            token.get_past_votes(OWNER, U256::from(1)),
+            U256::from(1_000_000)
+        );
+        assert_eq!(
+            token.get_past_votes(OWNER, U256::from(4)),
+            U256::from(1_000_000)
+        );
+        assert_eq!(
+            token.get_past_votes(OWNER, U256::from(5)),
+            U256::from(999_900)
+        );
+        assert_eq!(token.get_past_votes(ALICE, U256::from(9)), U256::ZERO);
+        assert_eq!(
+            token.get_past_total_supply(U256::from(9)),
+            U256::from(1_000_000)
+        );
+
+        let data = revert_data(&sdk, || {
+            token.get_past_votes(OWNER, U256::from(10));
+        });
+        assert_eq!(
+            data,
+            TokenError::FutureLookup {
+                timepoint: U256::from(10),
+                clock: 10,
+            }
+            .abi_encode()
+        );
+    }
+
+    #[test]
+    fn declared_interfaces_are_routed() {
+        let routed = [
+            SupportsInterfaceCall::SELECTOR,
+            TotalSupplyCall::SELECTOR,
+            BalanceOfCall::SELECTOR,
+            TransferCall::SELECTOR,
+            AllowanceCall::SELECTOR,
+            ApproveCall::SELECTOR,
+            TransferFromCall::SELECTOR,
+            NameCall::SELECTOR,
+            SymbolCall::SELECTOR,
+            DecimalsCall::SELECTOR,
+            PermitCall::SELECTOR,
+            NoncesCall::SELECTOR,
+            DomainSeparatorCall::SELECTOR,
+            HasRoleCall::SELECTOR,
+            GetRoleAdminCall::SELECTOR,
+            GrantRoleCall::SELECTOR,
+            RevokeRoleCall::SELECTOR,
+            RenounceRoleCall::SELECTOR,
+            ClockCall::SELECTOR,
+            ClockModeCall::SELECTOR,
+            GetVotesCall::SELECTOR,
+            GetPastVotesCall::SELECTOR,
+            GetPastTotalSupplyCall::SELECTOR,
+            DelegatesCall::SELECTOR,
+            DelegateCall::SELECTOR,
+            DelegateBySigCall::SELECTOR,
+            MaxFlashLoanCall::SELECTOR,
+            FlashFeeCall::SELECTOR,
+            FlashLoanCall::SELECTOR,
+            TransferAndCallCall::SELECTOR,
+            TransferAndCallWithDataCall::SELECTOR,
+            TransferFromAndCallCall::SELECTOR,
+            TransferFromAndCallWithDataCall::SELECTOR,
+            ApproveAndCallCall::SELECTOR,
+            ApproveAndCallWithDataCall::SELECTOR,
+        ];
+        for selectors in interfaces::INTERFACES {
+            for selector in selectors.iter() {
+                assert!(routed.contains(selector), "{selector:02x?} is not routed");
+            }
+        }
+
+        let (_sdk, token) = deploy(constructor_args());
+        for id in interfaces::SUPPORTED_INTERFACES {
+            assert!(token.supports_interface(FixedBytes(id)));
+        }
+        assert!(token.supports_interface(FixedBytes(hex!("36372b07"))));
+        assert!(!token.supports_interface(FixedBytes(hex!("ffffffff"))));
+    }
+
+    const TREASURY: Address = Address::repeat_byte(0x7e);
+
+    #[test]
+    fn transfer_fee_amounts_are_exact() {
+        let (sdk, mut token) = deploy(constructor_args());
+        token.set_transfer_fee(U256::from(250), TREASURY);
+
+        token.transfer(ALICE, U256::from(10_000));
+        assert_eq!(token.balance_of(ALICE), U256::from(9_750));
+        assert_eq!(token.balance_of(TREASURY), U256::from(250));
+        assert_eq!(token.balance_of(OWNER), U256::from(990_000));
+
+        // 2.5% of 1_001 is 25.025; the fee rounds down.
+        set_caller(&sdk, ALICE);
+        token.transfer(BOB, U256::from(1_001));
+        assert_eq!(token.balance_of(BOB), U256::from(976));
+        assert_eq!(token.balance_of(TREASURY), U256::from(275));
+
+        token.transfer(BOB, U256::from(39));
+        assert_eq!(token.balance_of(BOB), U256::from(1_015));
+        assert_eq!(token.balance_of(TREASURY), U256::from(275));
+
+        token.approve(OWNER, U256::from(2_000));
+        set_caller(&sdk, OWNER);
+        token.transfer_from(ALICE, BOB, U256::from(2_000));
+        assert_eq!(token.allowance(ALICE, OWNER), U256::ZERO);
+        assert_eq!(token.balance_of(BOB), U256::from(2_965));
+        assert_eq!(token.balance_of(TREASURY), U256::from(325));
+
+        token.set_fee_exempt(BOB, true);
+        set_caller(&sdk, ALICE);
+        token.transfer(BOB, U256::from(1_000));
+        assert_eq!(token.balance_of(BOB), U256::from(3_965));
+        assert_eq!(token.balance_of(TREASURY), U256::from(325));
+
+        let accounts = [OWNER, ALICE, BOB, TREASURY];
+        assert_eq!(sum_of_balances(&token, &accounts), token.total_supply());
+        assert_eq!(token.total_supply(), U256::from(1_000_000));
+
+        set_caller(&sdk, OWNER);
+        let data = revert_data(&sdk, || token.set_transfer_fee(U256::from(1_001), TREASURY));
+        assert_eq!(
+            data,
+            TokenError::InvalidTransferFee {
+                basis_points: U256::from(1_001),
+                treasury: TREASURY,
+            }
+            .abi_encode()
+        );
+    }
+
+    #[test]
+    fn increasing_an_allowance_screens_both_parties() {
+        let (sdk, mut token) = deploy(constructor_args());
+        token.set_blocked(BOB, true);
+
+        let data = revert_data(&sdk, || {
+            token.increase_allowance(BOB, U256::from(1));
+        });
+        assert_eq!(data, TokenError::BlockedAccount(BOB).abi_encode());
+
+        token.set_allowlist_only(true);
+        token.set_allowlisted(OWNER, true);
+        let data = revert_data(&sdk, || {
+            token.increase_allowance(ALICE, U256::from(1));
+        });
+        assert_eq!(data, TokenError::NotAllowlisted(ALICE).abi_encode());
+
+        // Decreasing only lowers exposure, so it stays open.
+        token.set_allowlist_only(false);
+        token.approve(ALICE, U256::from(10));
+        token.set_allowlist_only(true);
+        token.decrease_allowance(ALICE, U256::from(10));
+        assert_eq!(token.allowance(OWNER, ALICE), U256::ZERO);
+    }
+
+    #[test]
+    fn seize_ignores_pause_and_vesting_locks() {
+        let (sdk, mut token) = deploy(constructor_args());
+        token.create_vesting_schedule(ALICE, 1_000, 100, 1_000, U256::from(5_000));
+        token.transfer(ALICE, U256::from(1_000));
+        token.set_blocked(ALICE, true);
+        token.pause();
+
+        token.seize(ALICE, BOB, U256::from(3_000));
+        assert_eq!(token.balance_of(ALICE), U256::from(3_000));
+        assert_eq!(token.locked_balance_of(ALICE), U256::from(3_000));
+        assert_eq!(token.released(ALICE), U256::from(2_000));
+
+        token.seize(ALICE, BOB, U256::from(3_000));
+        assert_eq!(token.balance_of(ALICE), U256::ZERO);
+        assert_eq!(token.balance_of(BOB), U256::from(6_000));
+        assert_eq!(token.locked_balance_of(ALICE), U256::ZERO);
+        assert_eq!(token.total_supply(), U256::from(1_000_000));
+
+        let data = revert_data(&sdk, || token.seize(BOB, OWNER, U256::from(1)));
+        assert_eq!(data, TokenError::AccountNotBlocked(BOB).abi_encode());
+
+        set_caller(&sdk, BOB);
+        let data = revert_data(&sdk, || {
+            token.transfer(OWNER, U256::from(1));
+        });
+        assert_eq!(data, TokenError::EnforcedPause.abi_encode());
+
+        // Nothing is left of the schedule, so later deposits stay free.
+        set_caller(&sdk, OWNER);
+        token.unpause();
+        token.set_blocked(ALICE, false);
+        set_block(&sdk, 2, 2_500);
+        assert_eq!(token.releasable(ALICE), U256::ZERO);
+        token.transfer(ALICE, U256::from(100));
+        set_caller(&sdk, ALICE);
+        assert_eq!(token.release(), U256::ZERO);
+        token.transfer(BOB, U256::from(100));
+        assert_eq!(token.balance_of(ALICE), U256::ZERO);
+    }
+
+    #[test]
+    fn vesting_unlocks_linearly_after_the_cliff() {
+        let (sdk, mut token) = deploy(constructor_args());
+        token.set_blocked(BOB, true);
+        let data = revert_data(&sdk, || {
+            token.create_vesting_schedule(BOB, 1_000, 100, 1_000, U256::from(10_000))
+        });
+        assert_eq!(data, TokenError::BlockedAccount(BOB).abi_encode());
+        token.set_blocked(BOB, false);
+
+        token.create_vesting_schedule(ALICE, 1_000, 100, 1_000, U256::from(10_000));
+        assert_eq!(token.balance_of(ALICE), U256::from(10_000));
+        assert_eq!(token.locked_balance_of(ALICE), U256::from(10_000));
+
+        set_caller(&sdk, ALICE);
+        set_block(&sdk, 2, 1_099);
+        assert_eq!(token.releasable(ALICE), U256::ZERO);
+        assert_eq!(token.release(), U256::ZERO);
+        let data = revert_data(&sdk, || {
+            token.transfer(BOB, U256::from(1));
+        });
+        let locked = TokenError::LockedBalance {
+            account: ALICE,
+            unlocked: U256::ZERO,
+            needed: U256::from(1),
+        };
+        assert_eq!(data, locked.clone().abi_encode());
+
+        set_block(&sdk, 3, 1_100);
+        assert_eq!(token.releasable(ALICE), U256::from(1_000));
+
+        // 10_000 * 333 / 1_000, rounded down.
+        set_block(&sdk, 4, 1_333);
+        assert_eq!(token.releasable(ALICE), U256::from(3_330));
+        assert_eq!(token.release(), U256::from(3_330));
+        assert_eq!(token.released(ALICE), U256::from(3_330));
+        assert_eq!(token.locked_balance_of(ALICE), U256::from(6_670));
+        token.transfer(BOB, U256::from(3_330));
+        assert_eq!(token.balance_of(BOB), U256::from(3_330));
+        let data = revert_data(&sdk, || {
+            token.transfer(BOB, U256::from(1));
+        });
+        assert_eq!(data, locked.abi_encode());
+
+        set_block(&sdk, 5, 2_500);
+        assert_eq!(token.vested_amount(ALICE, 2_500), U256::from(10_000));
+        assert_eq!(token.releasable(ALICE), U256::from(6_670));
+        assert_eq!(token.release(), U256::from(6_670));
+        assert_eq!(token.locked_balance_of(ALICE), U256::ZERO);
+        token.transfer(BOB, U256::from(6_670));
+        assert_eq!(token.balance_of(ALICE), U256::ZERO);
+        assert_eq!(token.balance_of(BOB), U256::from(10_000));
+    }
+
+    fn rebasing_args() -> ConstructorArgs {
+        ConstructorArgs {
+            rebasing: true,
+            ..constructor_args()
+        }
+    }
+
+    #[test]
+    fn rebase_needs_rebasing_mode_and_shares() {
+        let (sdk, mut token) = deploy(constructor_args());
+        let data = revert_data(&sdk, || token.rebase(U256::from(2_000_000)));
+        assert_eq!(data, TokenError::RebasingDisabled.abi_encode());
+
+        let (sdk, mut token) = deploy(ConstructorArgs {
+            initialSupply: U256::ZERO,
+            ..rebasing_args()
+        });
+        let data = revert_data(&sdk, || token.rebase(U256::from(1_000)));
+        let empty_pool = TokenError::InvalidRebase {
+            total_shares: U256::ZERO,
+            new_total: U256::from(1_000),
+        };
+        assert_eq!(data, empty_pool.abi_encode());
+
+        let (sdk, mut token) = deploy(rebasing_args());
+        let data = revert_data(&sdk, || token.rebase(U256::ZERO));
+        let zero_total = TokenError::InvalidRebase {
+            total_shares: U256::from(1_000_000),
+            new_total: U256::ZERO,
+        };
+        assert_eq!(data, zero_total.abi_encode());
+
+        token.rebase(U256::from(2_000_000));
+        assert_eq!(token.total_supply(), U256::from(2_000_000));
+        assert_eq!(token.balance_of(OWNER), U256::from(2_000_000));
+        assert_eq!(token.shares_of(OWNER), U256::from(1_000_000));
+    }
+
+    #[test]
+    fn flash_loans_are_off_in_rebasing_mode() {
+        let (_, token) = deploy(constructor_args());
+        assert_eq!(token.max_flash_loan(TOKEN), U256::from(9_000_000));
+        assert_eq!(token.max_flash_loan(ALICE), U256::ZERO);
+
+        let (sdk, mut token) = deploy(rebasing_args());
+        assert_eq!(token.max_flash_loan(TOKEN), U256::ZERO);
+        let data = revert_data(&sdk, || {
+            token.flash_loan(ALICE, TOKEN, U256::from(1), Bytes::new());
+        });
+        assert_eq!(data, TokenError::ExceededMaxLoan(U256::ZERO).abi_encode());
+    }
+
+    #[test]
+    fn rebasing_snapshots_report_tokens() {
+        let (_, mut token) = deploy(rebasing_args());
+        token.transfer(ALICE, U256::from(250_000));
+        let first = token.snapshot();
+
+        token.rebase(U256::from(2_000_000));
+        assert_eq!(token.balance_of(ALICE), U256::from(500_000));
+        assert_eq!(token.balance_of_at(ALICE, first), U256::from(250_000));
+        assert_eq!(token.total_supply_at(first), U256::from(1_000_000));
+
+        token.mint(BOB, U256::from(1_000));
+        assert_eq!(token.shares_of(BOB), U256::from(500));
+        let second = token.snapshot();
+
+        token.rebase(U256::from(1_000_500));
+        assert_eq!(token.balance_of(BOB), U256::from(500));
+        assert_eq!(token.balance_of_at(BOB, second), U256::from(1_000));
+        assert_eq!(token.balance_of_at(ALICE, second), U256::from(500_000));
+        assert_eq!(token.total_supply_at(second), U256::from(2_001_000));
+        assert_eq!(token.balance_of_at(ALICE, first), U256::from(250_000));
+        assert_eq!(token.total_supply_at(first), U256::from(1_000_000));
+    }
+
+    #[test]
+    fn vesting_locks_follow_rebases() {
+        let (sdk, mut token) = deploy(rebasing_args());
+        token.create_vesting_schedule(ALICE, 1_000, 100, 1_000, U256::from(10_000));
+        token.transfer(ALICE, U256::from(1_000));
+
+        token.rebase(U256::from(500_000));
+        assert_eq!(token.balance_of(ALICE), U256::from(5_500));
+        assert_eq!(token.locked_balance_of(ALICE), U256::from(5_000));
+
+        set_caller(&sdk, ALICE);
+        let data = revert_data(&sdk, || {
+            token.transfer(BOB, U256::from(501));
+        });
+        let locked = TokenError::LockedBalance {
+            account: ALICE,
+            unlocked: U256::from(500),
+            needed: U256::from(501),
+        };
+        assert_eq!(data, locked.abi_encode());
+        token.transfer(BOB, U256::from(500));
+
+        set_block(&sdk, 2, 1_500);
+        assert_eq!(token.releasable(ALICE), U256::from(2_500));
+        set_caller(&sdk, OWNER);
+        token.rebase(U256::from(1_000_000));
+        set_caller(&sdk, ALICE);
+        assert_eq!(token.releasable(ALICE), U256::from(5_000));
+        assert_eq!(token.release(), U256::from(5_000));
+        assert_eq!(token.locked_balance_of(ALICE), U256::from(5_000));
+        token.transfer(BOB, U256::from(5_000));
+        assert_eq!(token.balance_of(ALICE), U256::from(5_000));
+    }
+
+    #[test]
+    fn rebasing_conversions_round_down() {
+        let (sdk, mut token) = deploy(rebasing_args());
+        token.transfer(ALICE, U256::from(3));
+        token.rebase(U256::from(1_500_000));
+
+        // 3 shares are worth 4.5 tokens and 999_997 are worth 1_499_995.5.
+        assert_eq!(token.balance_of(ALICE), U256::from(4));
+        assert_eq!(token.balance_of(OWNER), U256::from(1_499_995));
+        assert_eq!(
+            token.get_pooled_token_by_shares(U256::from(1)),
+            U256::from(1)
+        );
+        assert_eq!(token.get_shares_by_pooled_token(U256::from(1)), U256::ZERO);
+        // Balances never add up to more than the supply; the dust stays in the pool.
+        let balances = token.balance_of(OWNER) + token.balance_of(ALICE);
+        assert_eq!(token.total_supply() - balances, U256::from(1));
+
+        // Senders and burners give up shares rounded up, so even one token
+        // costs a whole share and nobody can burn supply for free.
+        set_caller(&sdk, ALICE);
+        token.transfer(BOB, U256::from(1));
+        assert_eq!(token.shares_of(ALICE), U256::from(2));
+        assert_eq!(token.shares_of(BOB), U256::from(1));
+        assert_eq!(token.balance_of(ALICE), U256::from(3));
+        assert_eq!(token.balance_of(BOB), U256::from(1));
+
+        token.burn(U256::from(1));
+        assert_eq!(token.shares_of(ALICE), U256::from(1));
+        assert_eq!(token.total_shares(), U256::from(999_999));
+        assert_eq!(token.total_supply(), U256::from(1_499_999));
+        assert_eq!(token.balance_of(OWNER), U256::from(1_499_995));
+
+        let data = revert_data(&sdk, || {
+            token.transfer(BOB, U256::from(2));
+        });
+        let insufficient = TokenError::InsufficientBalance {
+            sender: ALICE,
+            balance: U256::from(1),
+            needed: U256::from(2),
+        };
+        assert_eq!(data, insufficient.abi_encode());
+    }
+
+    #[test]
+    fn mul_div_uses_the_full_product() {
+        assert_eq!(mul_div(U256::MAX, U256::MAX, U256::MAX), Some(U256::MAX));
+        assert_eq!(mul_div(U256::MAX, U256::from(6), U256::from(3)), None);
+        let half = U256::MAX / U256::from(2);
+        assert_eq!(
+            mul_div(half, U256::from(4), U256::from(8)),
+            Some(half / U256::from(2))
+        );
+        assert_eq!(
+            mul_div(U256::from(7), U256::from(3), U256::from(2)),
+            Some(U256::from(10))
+        );
+        assert_eq!(
+            mul_div_up(U256::from(7), U256::from(3), U256::from(2)),
+            Some(U256::from(11))
+        );
+        assert_eq!(
+            mul_div_up(U256::from(6), U256::from(3), U256::from(2)),
+            Some(U256::from(9))
+        );
+        assert_eq!(
+            mul_div_up(U256::MAX, U256::from(1), U256::from(1)),
+            Some(U256::MAX)
+        );
+        assert_eq!(mul_div(U256::from(1), U256::from(1), U256::ZERO), None);
+    }
+
+    #[test]
+    fn mints_and_fees_screen_their_recipients() {
+        let (sdk, mut token) = deploy(constructor_args());
+        token.set_blocked(ALICE, true);
+        let data = revert_data(&sdk, || token.mint(ALICE, U256::from(1)));
+        assert_eq!(data, TokenError::BlockedAccount(ALICE).abi_encode());
+        let data = revert_data(&sdk, || {
+            token.flash_loan(ALICE, TOKEN, U256::from(1), Bytes::new());
+        });
+        assert_eq!(data, TokenError::BlockedAccount(ALICE).abi_encode());
+
+        token.set_allowlist_only(true);
+        token.set_allowlisted(OWNER, true);
+        token.set_allowlisted(BOB, true);
+        let data = revert_data(&sdk, || token.mint(TREASURY, U256::from(1)));
+        assert_eq!(data, TokenError::NotAllowlisted(TREASURY).abi_encode());
+
+        token.set_transfer_fee(U256::from(100), TREASURY);
+        let data = revert_data(&sdk, || {
+            token.transfer(BOB, U256::from(1_000));
+        });
+        assert_eq!(data, TokenError::NotAllowlisted(TREASURY).abi_encode());
+
+        token.set_allowlisted(TREASURY, true);
+        token.transfer(BOB, U256::from(1_000));
+        assert_eq!(token.balance_of(TREASURY), U256::from(10));
+        assert_eq!(token.balance_of(ALICE), U256::ZERO);
+    }
+
+    const BORROWER: Address = Address::repeat_byte(0xb0);
+
+    /// A borrower that checks the loan, approves `repayment` (loan plus fee
+    /// unless overridden) and answers with `output`.
+    fn mock_borrower(
+        sdk: &HostTestingContext,
+        repayment: Option<U256>,
+        output: B256,
+    ) -> Rc<Cell<bool>> {
+        let called = Rc::new(Cell::new(false));
+        let ctx = sdk.clone();
+        let flag = called.clone();
+        mock_contract(BORROWER, move |input| {
+            let call =
+                flash_mint::IERC3156FlashBorrower::onFlashLoanCall::abi_decode(input).unwrap();
+            assert_eq!(call.initiator, ALICE);
+            assert_eq!(call.token, TOKEN);
+            assert_eq!(call.fee, U256::ZERO);
+            assert_eq!(call.data, Bytes::from_static(b"loan"));
+
+            let mut token = ERC20::new(ctx.clone());
+            assert_eq!(token.balance_of(BORROWER), call.amount);
+            set_caller(&ctx, BORROWER);
+            token.approve(TOKEN, repayment.unwrap_or(call.amount + call.fee));
+            set_caller(&ctx, ALICE);
+            flag.set(true);
+            Ok(Bytes::from(output.to_vec()))
+        });
+        called
+    }
+
+    #[test]
+    fn flash_loan_mints_calls_back_and_burns() {
+        let (sdk, mut token) = deploy(constructor_args());
+        let called = mock_borrower(&sdk, None, flash_mint::CALLBACK_SUCCESS);
+
+        set_caller(&sdk, ALICE);
+        token.flash_loan(
+            BORROWER,
+            TOKEN,
+            U256::from(5_000),
+            Bytes::from_static(b"loan"),
+        );
+        assert!(called.get());
+        assert_eq!(token.balance_of(BORROWER), U256::ZERO);
+        assert_eq!(token.allowance(BORROWER, TOKEN), U256::ZERO);
+        assert_eq!(token.total_supply(), U256::from(1_000_000));
+
+        let data = revert_data(&sdk, || {
+            token.flash_loan(BORROWER, TOKEN, U256::from(9_000_001), Bytes::new());
+        });
+        assert_eq!(
+            data,
+            TokenError::ExceededMaxLoan(U256::from(9_000_000)).abi_encode()
+        );
+    }
+
+    #[test]
+    fn flash_loan_rejects_a_wrong_answer() {
+        let (sdk, mut token) = deploy(constructor_args());
+        mock_borrower(&sdk, None, B256::ZERO);
+        set_caller(&sdk, ALICE);
+        let data = revert_data(&sdk, || {
+            token.flash_loan(
+                BORROWER,
+                TOKEN,
+                U256::from(5_000),
+                Bytes::from_static(b"loan"),
+            );
+        });
+        assert_eq!(
+            data,
+            TokenError::InvalidFlashReceiver(BORROWER).abi_encode()
+        );
+    }
+
+    #[test]
+    fn flash_loan_needs_the_repayment_approved() {
+        let (sdk, mut token) = deploy(constructor_args());
+        mock_borrower(&sdk, Some(U256::from(4_999)), flash_mint::CALLBACK_SUCCESS);
+        set_caller(&sdk, ALICE);
+        let data = revert_data(&sdk, || {
+            token.flash_loan(
+                BORROWER,
+                TOKEN,
+                U256::from(5_000),
+                Bytes::from_static(b"loan"),
+            );
+        });
+        let unpaid = TokenError::InsufficientAllowance {
+            spender: TOKEN,
+            allowance: U256::from(4_999),
+            needed: U256::from(5_000),
+        };
+        assert_eq!(data, unpaid.abi_encode());
+    }
+
+    #[test]
+    fn flash_loan_to_an_account_without_code_fails() {
+        let (sdk, mut token) = deploy(constructor_args());
+        set_caller(&sdk, ALICE);
+        let data = revert_data(&sdk, || {
+            token.flash_loan(BOB, TOKEN, U256::from(5_000), Bytes::new());
+        });
+        assert_eq!(data, TokenError::InvalidFlashReceiver(BOB).abi_encode());
+    }
+
+    const RECEIVER: Address = Address::repeat_byte(0xc0);
+
+    /// A contract that answers every call with `answer` as an ABI `bytes4`
+    /// and records its inputs.
+    fn mock_callback(target: Address, answer: [u8; 4]) -> Rc<RefCell<Vec<Vec<u8>>>> {
+        let inputs = Rc::new(RefCell::new(Vec::new()));
+        let seen = inputs.clone();
+        mock_contract(target, move |input| {
+            seen.borrow_mut().push(input.to_vec());
+            let mut word = [0u8; 32];
+            word[..4].copy_from_slice(&answer);
+            Ok(Bytes::from(word.to_vec()))
+        });
+        inputs
+    }
+
+    #[test]
+    fn transfer_and_call_notifies_the_receiver() {
+        let (sdk, mut token) = deploy(constructor_args());
+        let inputs = mock_callback(RECEIVER, erc1363::ON_TRANSFER_RECEIVED);
+
+        token.transfer_and_call_with_data(RECEIVER, U256::from(100), Bytes::from_static(b"hi"));
+        token.approve(ALICE, U256::from(50));
+        set_caller(&sdk, ALICE);
+        token.transfer_from_and_call(OWNER, RECEIVER, U256::from(50));
+        assert_eq!(token.balance_of(RECEIVER), U256::from(150));
+
+        let inputs = inputs.borrow();
+        let direct =
+            erc1363::IERC1363Receiver::onTransferReceivedCall::abi_decode(&inputs[0]).unwrap();
+        assert_eq!((direct.operator, direct.from), (OWNER, OWNER));
+        assert_eq!(direct.value, U256::from(100));
+        assert_eq!(direct.data, Bytes::from_static(b"hi"));
+        let delegated =
+            erc1363::IERC1363Receiver::onTransferReceivedCall::abi_decode(&inputs[1]).unwrap();
+        assert_eq!((delegated.operator, delegated.from), (ALICE, OWNER));
+        assert_eq!(delegated.value, U256::from(50));
+        assert!(delegated.data.is_empty());
+    }
+
+    #[test]
+    fn transfer_and_call_rejects_wrong_answers_and_accounts_without_code() {
+        let (sdk, mut token) = deploy(constructor_args());
+        mock_callback(RECEIVER, erc1363::ON_APPROVAL_RECEIVED);
+
+        let data = revert_data(&sdk, || {
+            token.transfer_and_call(RECEIVER, U256::from(1));
+        });
+        assert_eq!(
+            data,
+            TokenError::InvalidERC1363Receiver(RECEIVER).abi_encode()
+        );
+        let data = revert_data(&sdk, || {
+            token.transfer_and_call(BOB, U256::from(1));
+        });
+        assert_eq!(data, TokenError::InvalidERC1363Receiver(BOB).abi_encode());
+
+        token.approve(ALICE, U256::from(10));
+        set_caller(&sdk, ALICE);
+        let data = revert_data(&sdk, || {
+            token.transfer_from_and_call_with_data(OWNER, BOB, U256::from(1), Bytes::new());
+        });
+        assert_eq!(data, TokenError::InvalidERC1363Receiver(BOB).abi_encode());
+    }
+
+    #[test]
+    fn approve_and_call_notifies_the_spender() {
+        let (sdk, mut token) = deploy(constructor_args());
+        let inputs = mock_callback(RECEIVER, erc1363::ON_APPROVAL_RECEIVED);
+
+        token.approve_and_call_with_data(RECEIVER, U256::from(70), Bytes::from_static(b"go"));
+        assert_eq!(token.allowance(OWNER, RECEIVER), U256::from(70));
+        let call =
+            erc1363::IERC1363Spender::onApprovalReceivedCall::abi_decode(&inputs.borrow()[0])
+                .unwrap();
+        assert_eq!(call.owner, OWNER);
+        assert_eq!(call.value, U256::from(70));
+        assert_eq!(call.data, Bytes::from_static(b"go"));
+
+        mock_callback(RECEIVER, erc1363::ON_TRANSFER_RECEIVED);
+        let data = revert_data(&sdk, || {
+            token.approve_and_call(RECEIVER, U256::from(1));
+        });
+        assert_eq!(
+            data,
+            TokenError::InvalidERC1363Spender(RECEIVER).abi_encode()
+        );
+        let data = revert_data(&sdk, || {
+            token.approve_and_call(BOB, U256::from(1));
+        });
+        assert_eq!(data, TokenError::InvalidERC1363Spender(BOB).abi_encode());
+    }
+
+    #[test]
+    fn batch_transfer_is_all_or_nothing() {
+        let (sdk, mut token) = deploy(constructor_args());
+        token.batch_transfer(vec![ALICE, BOB], vec![U256::from(10), U256::from(20)]);
+        assert_eq!(token.balance_of(ALICE), U256::from(10));
+        assert_eq!(token.balance_of(BOB), U256::from(20));
+
+        let data = revert_data(&sdk, || {
+            token.batch_transfer(vec![ALICE, BOB], vec![U256::from(1)]);
+        });
+        let mismatch = TokenError::BatchLengthMismatch {
+            recipients: 2,
+            values: 1,
+        };
+        assert_eq!(data, mismatch.abi_encode());
+
+        // The host rolls back the transfers before the failing one.
+        let data = revert_data(&sdk, || {
+            token.batch_transfer(
+                vec![ALICE, Address::ZERO],
+                vec![U256::from(1), U256::from(1)],
+            );
+        });
+        assert_eq!(
+            data,
+            TokenError::InvalidReceiver(Address::ZERO).abi_encode()
+        );
+    }
+
+    /// Runs the token's own router for delegate calls to itself.
+    fn mock_self(sdk: &HostTestingContext) {
+        let ctx = sdk.clone();
+        mock_contract(TOKEN, move |input| {
+            let mut token = ERC20::new(ctx.clone().with_input(input.to_vec()));
+            let _ = ctx.take_output();
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| token.main()));
+            let output = Bytes::from(ctx.take_output());
+            match result {
+                Ok(()) => Ok(output),
+                Err(_) => Err(output),
+            }
+        });
+    }
+
+    #[test]
+    fn multicall_keeps_the_caller_and_forwards_reverts() {
+        let (sdk, mut token) = deploy(constructor_args());
+        mock_self(&sdk);
+
+        let results = token.multicall(vec![
+            ApproveCall {
+                spender: ALICE,
+                value: U256::from(5),
+            }
+            .abi_encode()
+            .into(),
+            TransferCall {
+                to: BOB,
+                value: U256::from(7),
+            }
+            .abi_encode()
+            .into(),
+        ]);
+        assert_eq!(token.allowance(OWNER, ALICE), U256::from(5));
+        assert_eq!(token.balance_of(BOB), U256::from(7));
+        assert_eq!(token.balance_of(OWNER), U256::from(999_993));
+        let success = Bytes::from(U256::from(1).to_be_bytes::<32>().to_vec());
+        assert_eq!(results, vec![success.clone(), success]);
+
+        let data = revert_data(&sdk, || {
+            token.multicall(vec![
+                TransferCall {
+                    to: BOB,
+                    value: U256::from(1),
+                }
+                .abi_encode()
+                .into(),
+                TransferCall {
+                    to: Address::ZERO,
+                    value: U256::from(1),
+                }
+                .abi_encode()
+                .into(),
+            ]);
+        });
+        assert_eq!(
+            data,
+            TokenError::InvalidReceiver(Address::ZERO).abi_encode()
+        );
+    }
+
+    #[test]
+    fn zero_addresses_revert_with_their_role() {
+        let (sdk, mut token) = deploy(constructor_args());
+        let zero = Address::ZERO;
+        let receiver = TokenError::InvalidReceiver(zero).abi_encode();
+        let sender = TokenError::InvalidSender(zero).abi_encode();
+
+        let data = revert_data(&sdk, || {
+            token.transfer(zero, U256::from(1));
+        });
+        assert_eq!(data, receiver);
+        let data = revert_data(&sdk, || token.mint(zero, U256::from(1)));
+        assert_eq!(data, receiver);
+        let data = revert_data(&sdk, || {
+            token.approve(zero, U256::from(1));
+        });
+        assert_eq!(data, TokenError::InvalidSpender(zero).abi_encode());
+
+        // A zero value gets past the allowance check.
+        let data = revert_data(&sdk, || {
+            token.transfer_from(zero, BOB, U256::ZERO);
+        });
+        assert_eq!(data, sender);
+        let data = revert_data(&sdk, || token.burn_from(zero, U256::ZERO));
+        assert_eq!(data, sender);
+
+        set_caller(&sdk, zero);
+        let data = revert_data(&sdk, || {
+            token.transfer(BOB, U256::ZERO);
+        });
+        assert_eq!(data, sender);
+        let data = revert_data(&sdk, || token.burn(U256::ZERO));
+        assert_eq!(data, sender);
+        let data = revert_data(&sdk, || {
+            token.approve(BOB, U256::from(1));
+        });
+        assert_eq!(data, TokenError::InvalidApprover(zero).abi_encode());
+    }
+
+    #[test]
+    fn infinite_allowances_are_not_spent() {
+        let (sdk, mut token) = deploy(constructor_args());
+        token.approve(ALICE, U256::MAX);
+        let _ = sdk.take_logs();
+
+        set_caller(&sdk, ALICE);
+        token.transfer_from(OWNER, BOB, U256::from(10));
+        token.burn_from(OWNER, U256::from(5));
+        assert_eq!(token.allowance(OWNER, ALICE), U256::MAX);
+        assert_eq!(token.balance_of(OWNER), U256::from(999_985));
+
+        let events: Vec<B256> = sdk
+            .take_logs()
+            .into_iter()
+            .map(|(_, topics)| topics[0])
+            .collect();
+        assert_eq!(events, vec![Transfer::SIGNATURE_HASH; 2]);
+    }
+
+    #[test]
+    fn supply_is_capped() {
+        let (sdk, mut token) = deploy(constructor_args());
+        let data = revert_data(&sdk, || token.mint(ALICE, U256::from(9_000_001)));
+        let exceeded = TokenError::ExceededCap {
+            increased_supply: U256::from(10_000_001),
+            cap: U256::from(10_000_000),
+        };
+        assert_eq!(data, exceeded.abi_encode());
+        token.mint(ALICE, U256::from(9_000_000));
+        assert_eq!(token.total_supply(), token.cap());
+
+        let args = ConstructorArgs {
+            cap: U256::ZERO,
+            ..constructor_args()
+        };
+        let sdk = HostTestingContext::default()
+            .with_input(<ConstructorArgs as SolType>::abi_encode_params(&args));
+        set_caller(&sdk, OWNER);
+        let mut token = ERC20::new(sdk.clone());
+        assert_eq!(
+            revert_data(&sdk, || token.deploy()),
+            TokenError::InvalidCap(U256::ZERO).abi_encode()
+        );
+    }
+
+    // PERMIT_SIGNER delegating to ALICE with expiry 2_000, at nonces 0 and 1.
+    const DELEGATION_DIGEST: B256 = B256::new(hex!(
+        "4b3417901f62573986a7c2e3af488d4e74d25cbf792b659116f627972b2c52d0"
+    ));
+    const DELEGATION_R: B256 = B256::new(hex!(
+        "1af5006a4a3ad4e639585a7ef9dc52af09fd5e8dc039937797c15485d97d65b8"
+    ));
+    const DELEGATION_S: B256 = B256::new(hex!(
+        "1b34789b0a1e9f1641e4524d291a6d397b7f9162918b3780f69849c71473f9c3"
+    ));
+    const DELEGATION_NEXT_R: B256 = B256::new(hex!(
+        "4d6f70dd63e95a836e69e6d5ef61e2adcb47b3a1126a1354c40b2c41ae4bf71a"
+    ));
+    const DELEGATION_NEXT_S: B256 = B256::new(hex!(
+        "316a23af465487ce3276061139de23256a347fa9d1d9093b584f2babee508cdf"
+    ));
+
+    #[test]
+    fn delegate_by_sig_matches_reference_vector() {
+        let (sdk, mut token) = deploy(constructor_args());
+        token.transfer(PERMIT_SIGNER, U256::from(500));
+        let expiry = U256::from(2_000);
+        let digest = Delegation {
+            delegatee: ALICE,
+            nonce: U256::ZERO,
+            expiry,
+        }
+        .eip712_signing_hash(&token.eip712_domain());
+        assert_eq!(digest, DELEGATION_DIGEST);
+
+        // Signed for nonce 1 while the signer is still at 0.
+        set_caller(&sdk, BOB);
+        let data = revert_data(&sdk, || {
+            token.delegate_by_sig(
+                ALICE,
+                U256::from(1),
+                expiry,
+                28,
+                DELEGATION_NEXT_R,
+                DELEGATION_NEXT_S,
+            )
+        });
+        let stale = TokenError::InvalidAccountNonce {
+            account: PERMIT_SIGNER,
+            current_nonce: U256::ZERO,
+        };
+        assert_eq!(data, stale.abi_encode());
+
+        // The failed call above bumped the nonce; reverts roll that back on
+        // chain but not here.
+        let (sdk, mut token) = deploy(constructor_args());
+        token.transfer(PERMIT_SIGNER, U256::from(500));
+        set_block(&sdk, 2, 2_001);
+        let data = revert_data(&sdk, || {
+            token.delegate_by_sig(ALICE, U256::ZERO, expiry, 28, DELEGATION_R, DELEGATION_S)
+        });
+        assert_eq!(data, TokenError::VotesExpiredSignature(expiry).abi_encode());
+
+        set_block(&sdk, 2, 2_000);
+        set_caller(&sdk, BOB);
+        token.delegate_by_sig(ALICE, U256::ZERO, expiry, 28, DELEGATION_R, DELEGATION_S);
+        assert_eq!(token.delegates(PERMIT_SIGNER), ALICE);
+        assert_eq!(token.get_votes(ALICE), U256::from(500));
+        assert_eq!(token.nonces(PERMIT_SIGNER), U256::from(1));
+    }
+}
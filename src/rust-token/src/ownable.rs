@@ -0,0 +1,88 @@
+//! Single owner with two-step transfer, like OpenZeppelin's `Ownable2Step`.
+
+use crate::{emit_event, revert};
+use alloy_sol_types::sol;
+use fluentbase_sdk::{derive::solidity_storage, Address, ContextReader, SharedAPI};
+
+sol! {
+    event OwnershipTransferred(address indexed previousOwner, address indexed newOwner);
+    event OwnershipTransferStarted(address indexed previousOwner, address indexed newOwner);
+
+    error OwnableUnauthorizedAccount(address account);
+    error OwnableInvalidOwner(address owner);
+}
+
+solidity_storage! {
+    Address Owner;
+    Address PendingOwner;
+}
+
+pub fn initialize<SDK: SharedAPI>(sdk: &mut SDK, initial_owner: Address) {
+    if initial_owner == Address::ZERO {
+        revert(
+            sdk,
+            OwnableInvalidOwner {
+                owner: initial_owner,
+            },
+        );
+    }
+    set_owner(sdk, initial_owner);
+}
+
+pub fn owner<SDK: SharedAPI>(sdk: &SDK) -> Address {
+    Owner::get(sdk)
+}
+
+pub fn pending_owner<SDK: SharedAPI>(sdk: &SDK) -> Address {
+    PendingOwner::get(sdk)
+}
+
+pub fn only_owner<SDK: SharedAPI>(sdk: &mut SDK) {
+    let caller = sdk.context().contract_caller();
+    if caller != Owner::get(sdk) {
+        revert(sdk, OwnableUnauthorizedAccount { account: caller });
+    }
+}
+
+/// `new_owner` has to [`accept_ownership`]; the zero address cancels.
+pub fn start_ownership_transfer<SDK: SharedAPI>(sdk: &mut SDK, new_owner: Address) {
+    only_owner(sdk);
+    PendingOwner::set(sdk, new_owner);
+
+    let previous_owner = Owner::get(sdk);
+    emit_event(
+        sdk,
+        OwnershipTransferStarted {
+            previousOwner: previous_owner,
+            newOwner: new_owner,
+        },
+    );
+}
+
+pub fn accept_ownership<SDK: SharedAPI>(sdk: &mut SDK) {
+    let caller = sdk.context().contract_caller();
+    if caller != PendingOwner::get(sdk) {
+        revert(sdk, OwnableUnauthorizedAccount { account: caller });
+    }
+    set_owner(sdk, caller);
+}
+
+pub fn renounce_ownership<SDK: SharedAPI>(sdk: &mut SDK) {
+    only_owner(sdk);
+    set_owner(sdk, Address::ZERO);
+}
+
+/// Unchecked, like OpenZeppelin's `_transferOwnership`.
+fn set_owner<SDK: SharedAPI>(sdk: &mut SDK, new_owner: Address) {
+    let previous_owner = Owner::get(sdk);
+    PendingOwner::set(sdk, Address::ZERO);
+    Owner::set(sdk, new_owner);
+
+    emit_event(
+        sdk,
+        OwnershipTransferred {
+            previousOwner: previous_owner,
+            newOwner: new_owner,
+        },
+    );
+}
@@ -0,0 +1,41 @@
+//! Emergency stop flag, like OpenZeppelin's `Pausable`.
+
+use crate::{emit_event, revert};
+use alloy_sol_types::sol;
+use fluentbase_sdk::{derive::solidity_storage, ContextReader, SharedAPI};
+
+sol! {
+    event Paused(address account);
+    event Unpaused(address account);
+
+    error EnforcedPause();
+    error ExpectedPause();
+}
+
+solidity_storage! {
+    bool PausedState;
+}
+
+pub fn paused<SDK: SharedAPI>(sdk: &SDK) -> bool {
+    PausedState::get(sdk)
+}
+
+pub fn pause<SDK: SharedAPI>(sdk: &mut SDK) {
+    if paused(sdk) {
+        revert(sdk, EnforcedPause {});
+    }
+    PausedState::set(sdk, true);
+
+    let account = sdk.context().contract_caller();
+    emit_event(sdk, Paused { account });
+}
+
+pub fn unpause<SDK: SharedAPI>(sdk: &mut SDK) {
+    if !paused(sdk) {
+        revert(sdk, ExpectedPause {});
+    }
+    PausedState::set(sdk, false);
+
+    let account = sdk.context().contract_caller();
+    emit_event(sdk, Unpaused { account });
+}
@@ -0,0 +1,123 @@
+//! Share-based balances for rebasing mode: `shares * totalSupply / totalShares`, rounded down.
+
+use crate::{emit_event, error::TokenError, mul_div, mul_div_up};
+use alloy_sol_types::sol;
+use fluentbase_sdk::{derive::solidity_storage, Address, SharedAPI, U256};
+
+sol! {
+    event TransferShares(address indexed from, address indexed to, uint256 sharesValue);
+    event TokenRebased(uint256 previousTotalPooled, uint256 newTotalPooled, uint256 totalShares);
+
+    error RebasingDisabled();
+    error RebasingInvalidTotal(uint256 totalShares, uint256 newTotalPooled);
+}
+
+solidity_storage! {
+    bool Rebasing;
+    mapping(Address => U256) Shares;
+    U256 TotalShares;
+}
+
+pub fn initialize<SDK: SharedAPI>(sdk: &mut SDK, rebasing: bool) {
+    Rebasing::set(sdk, rebasing);
+}
+
+pub fn rebasing<SDK: SharedAPI>(sdk: &SDK) -> bool {
+    Rebasing::get(sdk)
+}
+
+pub fn shares_of<SDK: SharedAPI>(sdk: &SDK, account: Address) -> U256 {
+    Shares::get(sdk, account)
+}
+
+pub fn total_shares<SDK: SharedAPI>(sdk: &SDK) -> U256 {
+    TotalShares::get(sdk)
+}
+
+/// Shares worth `amount`, rounded down. One to one while the pool is empty.
+pub fn shares_by_pooled_token<SDK: SharedAPI>(
+    sdk: &SDK,
+    amount: U256,
+    total_pooled: U256,
+) -> Result<U256, TokenError> {
+    let total_shares = total_shares(sdk);
+    if total_shares.is_zero() || total_pooled.is_zero() {
+        return Ok(amount);
+    }
+    mul_div(amount, total_shares, total_pooled).ok_or(TokenError::ArithmeticOverflow)
+}
+
+/// Like [`shares_by_pooled_token`], rounded up.
+pub fn shares_by_pooled_token_up<SDK: SharedAPI>(
+    sdk: &SDK,
+    amount: U256,
+    total_pooled: U256,
+) -> Result<U256, TokenError> {
+    let total_shares = total_shares(sdk);
+    if total_shares.is_zero() || total_pooled.is_zero() {
+        return Ok(amount);
+    }
+    mul_div_up(amount, total_shares, total_pooled).ok_or(TokenError::ArithmeticOverflow)
+}
+
+/// Tokens that `shares` are worth, rounded down.
+pub fn pooled_token_by_shares<SDK: SharedAPI>(
+    sdk: &SDK,
+    shares: U256,
+    total_pooled: U256,
+) -> Result<U256, TokenError> {
+    pooled_token_by_shares_at(shares, total_shares(sdk), total_pooled)
+}
+
+/// Same conversion against past totals, e.g. those of a snapshot.
+pub fn pooled_token_by_shares_at(
+    shares: U256,
+    total_shares: U256,
+    total_pooled: U256,
+) -> Result<U256, TokenError> {
+    if total_shares.is_zero() {
+        return Ok(shares);
+    }
+    mul_div(shares, total_pooled, total_shares).ok_or(TokenError::ArithmeticOverflow)
+}
+
+/// Zero `from` issues shares and zero `to` retires them. `from` must hold enough.
+pub fn move_shares<SDK: SharedAPI>(
+    sdk: &mut SDK,
+    from: Address,
+    to: Address,
+    shares: U256,
+) -> Result<(), TokenError> {
+    if from == Address::ZERO {
+        let total = total_shares(sdk)
+            .checked_add(shares)
+            .ok_or(TokenError::ArithmeticOverflow)?;
+        TotalShares::set(sdk, total);
+    } else {
+        let balance = shares_of(sdk, from);
+        Shares::set(sdk, from, balance - shares);
+    }
+
+    if to == Address::ZERO {
+        let total = total_shares(sdk);
+        TotalShares::set(sdk, total - shares);
+    } else {
+        // Cannot overflow: no account holds more than `TotalShares`.
+        let balance = shares_of(sdk, to);
+        Shares::set(sdk, to, balance + shares);
+    }
+    Ok(())
+}
+
+/// Emits the rebase; the caller has already set the new total supply.
+pub fn rebased<SDK: SharedAPI>(sdk: &mut SDK, previous_total: U256, new_total: U256) {
+    let total_shares = total_shares(sdk);
+    emit_event(
+        sdk,
+        TokenRebased {
+            previousTotalPooled: previous_total,
+            newTotalPooled: new_total,
+            totalShares: total_shares,
+        },
+    );
+}
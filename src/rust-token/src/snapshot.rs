@@ -0,0 +1,95 @@
+//! Point-in-time balances, written lazily on the first change after each snapshot.
+
+use crate::{checkpoints::Trace, emit_event, error::TokenError};
+use alloy_sol_types::sol;
+use fluentbase_sdk::{derive::solidity_storage, Address, SharedAPI, U256};
+
+sol! {
+    event Snapshot(uint256 id);
+
+    error ERC20InvalidSnapshotId(uint256 id);
+}
+
+solidity_storage! {
+    U256 CurrentSnapshotId;
+}
+
+const BALANCE_NAMESPACE: u8 = 1;
+const TOTAL_SUPPLY_NAMESPACE: u8 = 2;
+const POOLED_TOTAL_NAMESPACE: u8 = 5;
+
+pub fn current_id<SDK: SharedAPI>(sdk: &SDK) -> U256 {
+    CurrentSnapshotId::get(sdk)
+}
+
+/// Starts a new snapshot and returns its id. Ids start at 1.
+pub fn snapshot<SDK: SharedAPI>(sdk: &mut SDK) -> U256 {
+    let id = current_id(sdk) + U256::from(1);
+    CurrentSnapshotId::set(sdk, id);
+    emit_event(sdk, Snapshot { id });
+    id
+}
+
+/// Must be called with the account's balance *before* it changes.
+pub fn update_account<SDK: SharedAPI>(sdk: &mut SDK, account: Address, balance: U256) {
+    update(sdk, Trace::new(BALANCE_NAMESPACE, account), balance);
+}
+
+/// Must be called with the total supply *before* it changes.
+pub fn update_total_supply<SDK: SharedAPI>(sdk: &mut SDK, total_supply: U256) {
+    update(
+        sdk,
+        Trace::new(TOTAL_SUPPLY_NAMESPACE, Address::ZERO),
+        total_supply,
+    );
+}
+
+/// Rebasing mode only, where the other traces hold shares. Must be called
+/// with the pooled total *before* it changes.
+pub fn update_pooled_total<SDK: SharedAPI>(sdk: &mut SDK, total_pooled: U256) {
+    update(
+        sdk,
+        Trace::new(POOLED_TOTAL_NAMESPACE, Address::ZERO),
+        total_pooled,
+    );
+}
+
+/// `None` if the balance has not changed since snapshot `id`.
+pub fn balance_at<SDK: SharedAPI>(
+    sdk: &SDK,
+    account: Address,
+    id: U256,
+) -> Result<Option<U256>, TokenError> {
+    value_at(sdk, Trace::new(BALANCE_NAMESPACE, account), id)
+}
+
+pub fn total_supply_at<SDK: SharedAPI>(sdk: &SDK, id: U256) -> Result<Option<U256>, TokenError> {
+    value_at(sdk, Trace::new(TOTAL_SUPPLY_NAMESPACE, Address::ZERO), id)
+}
+
+pub fn pooled_total_at<SDK: SharedAPI>(sdk: &SDK, id: U256) -> Result<Option<U256>, TokenError> {
+    value_at(sdk, Trace::new(POOLED_TOTAL_NAMESPACE, Address::ZERO), id)
+}
+
+fn update<SDK: SharedAPI>(sdk: &mut SDK, trace: Trace, current_value: U256) {
+    let id = current_id(sdk);
+    if id.is_zero() {
+        return;
+    }
+    let stale = match trace.latest(sdk) {
+        Some((last_id, _)) => last_id < id,
+        None => true,
+    };
+    if stale {
+        trace.push(sdk, id, current_value);
+    }
+}
+
+fn value_at<SDK: SharedAPI>(sdk: &SDK, trace: Trace, id: U256) -> Result<Option<U256>, TokenError> {
+    if id.is_zero() || id > current_id(sdk) {
+        return Err(TokenError::InvalidSnapshotId(id));
+    }
+    // The first value recorded at or after `id` is what the account held
+    // when snapshot `id` was taken.
+    Ok(trace.lower_lookup(sdk, id))
+}
@@ -0,0 +1,116 @@
+//! Linear vesting with a cliff; unreleased tokens stay locked in the beneficiary's balance.
+//! Amounts, events included, are balance units: shares in rebasing mode, tokens otherwise.
+
+use crate::{emit_event, error::TokenError};
+use alloy_sol_types::sol;
+use fluentbase_sdk::{derive::solidity_storage, Address, ContextReader, SharedAPI, U256};
+
+sol! {
+    event VestingScheduleCreated(address indexed beneficiary, uint64 start, uint64 cliff, uint64 duration, uint256 amount);
+    event TokensReleased(address indexed beneficiary, uint256 amount);
+
+    error VestingScheduleExists(address beneficiary);
+    error VestingInvalidSchedule(uint64 cliff, uint64 duration, uint256 amount);
+    error VestingLockedBalance(address account, uint256 unlocked, uint256 needed);
+}
+
+solidity_storage! {
+    mapping(Address => U256) VestingStart;
+    mapping(Address => U256) VestingCliff;
+    mapping(Address => U256) VestingDuration;
+    mapping(Address => U256) VestingAmount;
+    mapping(Address => U256) VestingReleased;
+}
+
+/// At most one schedule per beneficiary; the caller moves the tokens.
+pub fn create_schedule<SDK: SharedAPI>(
+    sdk: &mut SDK,
+    beneficiary: Address,
+    start: u64,
+    cliff: u64,
+    duration: u64,
+    amount: U256,
+) -> Result<(), TokenError> {
+    if !VestingAmount::get(sdk, beneficiary).is_zero() {
+        return Err(TokenError::VestingScheduleExists(beneficiary));
+    }
+    if amount.is_zero() || cliff > duration {
+        return Err(TokenError::InvalidVestingSchedule {
+            cliff,
+            duration,
+            amount,
+        });
+    }
+    VestingStart::set(sdk, beneficiary, U256::from(start));
+    VestingCliff::set(sdk, beneficiary, U256::from(cliff));
+    VestingDuration::set(sdk, beneficiary, U256::from(duration));
+    VestingAmount::set(sdk, beneficiary, amount);
+
+    emit_event(
+        sdk,
+        VestingScheduleCreated {
+            beneficiary,
+            start,
+            cliff,
+            duration,
+            amount,
+        },
+    );
+    Ok(())
+}
+
+/// Rounded down.
+pub fn vested_amount<SDK: SharedAPI>(sdk: &SDK, beneficiary: Address, timestamp: u64) -> U256 {
+    let amount = VestingAmount::get(sdk, beneficiary);
+    let start = VestingStart::get(sdk, beneficiary);
+    let timestamp = U256::from(timestamp);
+    if timestamp < start + VestingCliff::get(sdk, beneficiary) {
+        return U256::ZERO;
+    }
+    let duration = VestingDuration::get(sdk, beneficiary);
+    if timestamp >= start + duration {
+        return amount;
+    }
+    // Split so `amount * elapsed` cannot overflow; the remainder is below
+    // `duration`, which fits in 64 bits.
+    let elapsed = timestamp - start;
+    amount / duration * elapsed + amount % duration * elapsed / duration
+}
+
+pub fn released<SDK: SharedAPI>(sdk: &SDK, beneficiary: Address) -> U256 {
+    VestingReleased::get(sdk, beneficiary)
+}
+
+pub fn releasable<SDK: SharedAPI>(sdk: &SDK, beneficiary: Address) -> U256 {
+    let now = sdk.context().block_timestamp();
+    // Seizures count as released, so this can run ahead of vesting.
+    vested_amount(sdk, beneficiary, now).saturating_sub(released(sdk, beneficiary))
+}
+
+/// Unlocks everything vested so far and returns the newly released amount.
+pub fn release<SDK: SharedAPI>(sdk: &mut SDK, beneficiary: Address) -> U256 {
+    let amount = releasable(sdk, beneficiary);
+    let released = released(sdk, beneficiary) + amount;
+    VestingReleased::set(sdk, beneficiary, released);
+
+    emit_event(
+        sdk,
+        TokensReleased {
+            beneficiary,
+            amount,
+        },
+    );
+    amount
+}
+
+/// Counts up to `amount` of the locked balance as released, for tokens that
+/// were seized from `account`.
+pub fn forfeit<SDK: SharedAPI>(sdk: &mut SDK, account: Address, amount: U256) {
+    let released = released(sdk, account) + amount.min(locked(sdk, account));
+    VestingReleased::set(sdk, account, released);
+}
+
+/// The part of `account`'s balance that may not leave it yet.
+pub fn locked<SDK: SharedAPI>(sdk: &SDK, account: Address) -> U256 {
+    VestingAmount::get(sdk, account) - released(sdk, account)
+}
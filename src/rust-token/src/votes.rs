@@ -0,0 +1,147 @@
+//! ERC20Votes-style delegation, checkpointed by block number.
+
+use crate::{checkpoints::Trace, emit_event, error::TokenError};
+use alloy_sol_types::sol;
+use fluentbase_sdk::{derive::solidity_storage, Address, ContextReader, SharedAPI, U256};
+
+sol! {
+    event DelegateChanged(address indexed delegator, address indexed fromDelegate, address indexed toDelegate);
+    event DelegateVotesChanged(address indexed delegate, uint256 previousVotes, uint256 newVotes);
+
+    error ERC5805FutureLookup(uint256 timepoint, uint48 clock);
+    error VotesExpiredSignature(uint256 expiry);
+}
+
+solidity_storage! {
+    mapping(Address => Address) Delegates;
+}
+
+const VOTES_NAMESPACE: u8 = 3;
+const TOTAL_SUPPLY_NAMESPACE: u8 = 4;
+
+pub const CLOCK_MODE: &str = "mode=blocknumber&from=default";
+
+pub fn clock<SDK: SharedAPI>(sdk: &SDK) -> u64 {
+    sdk.context().block_number()
+}
+
+pub fn delegates<SDK: SharedAPI>(sdk: &SDK, account: Address) -> Address {
+    Delegates::get(sdk, account)
+}
+
+pub fn get_votes<SDK: SharedAPI>(sdk: &SDK, account: Address) -> U256 {
+    Trace::new(VOTES_NAMESPACE, account)
+        .latest(sdk)
+        .map_or(U256::ZERO, |(_, votes)| votes)
+}
+
+pub fn get_past_votes<SDK: SharedAPI>(
+    sdk: &SDK,
+    account: Address,
+    timepoint: U256,
+) -> Result<U256, TokenError> {
+    past_value(sdk, Trace::new(VOTES_NAMESPACE, account), timepoint)
+}
+
+pub fn get_past_total_supply<SDK: SharedAPI>(
+    sdk: &SDK,
+    timepoint: U256,
+) -> Result<U256, TokenError> {
+    past_value(
+        sdk,
+        Trace::new(TOTAL_SUPPLY_NAMESPACE, Address::ZERO),
+        timepoint,
+    )
+}
+
+pub fn delegate<SDK: SharedAPI>(
+    sdk: &mut SDK,
+    account: Address,
+    delegatee: Address,
+    balance: U256,
+) {
+    let previous_delegate = delegates(sdk, account);
+    Delegates::set(sdk, account, delegatee);
+
+    emit_event(
+        sdk,
+        DelegateChanged {
+            delegator: account,
+            fromDelegate: previous_delegate,
+            toDelegate: delegatee,
+        },
+    );
+    move_voting_power(sdk, previous_delegate, delegatee, balance);
+}
+
+/// Called from the token's `update`.
+pub fn transfer_voting_units<SDK: SharedAPI>(
+    sdk: &mut SDK,
+    from: Address,
+    to: Address,
+    amount: U256,
+) {
+    let supply = Trace::new(TOTAL_SUPPLY_NAMESPACE, Address::ZERO);
+    if from == Address::ZERO || to == Address::ZERO {
+        let current = supply.latest(sdk).map_or(U256::ZERO, |(_, total)| total);
+        let updated = if from == Address::ZERO {
+            current + amount
+        } else {
+            current - amount
+        };
+        let now = U256::from(clock(sdk));
+        supply.push(sdk, now, updated);
+    }
+
+    let (source, destination) = (delegates(sdk, from), delegates(sdk, to));
+    move_voting_power(sdk, source, destination, amount);
+}
+
+fn move_voting_power<SDK: SharedAPI>(sdk: &mut SDK, from: Address, to: Address, amount: U256) {
+    if from == to || amount.is_zero() {
+        return;
+    }
+    let now = U256::from(clock(sdk));
+    if from != Address::ZERO {
+        let previous_votes = get_votes(sdk, from);
+        let new_votes = previous_votes - amount;
+        Trace::new(VOTES_NAMESPACE, from).push(sdk, now, new_votes);
+        emit_event(
+            sdk,
+            DelegateVotesChanged {
+                delegate: from,
+                previousVotes: previous_votes,
+                newVotes: new_votes,
+            },
+        );
+    }
+    if to != Address::ZERO {
+        let previous_votes = get_votes(sdk, to);
+        let new_votes = previous_votes + amount;
+        Trace::new(VOTES_NAMESPACE, to).push(sdk, now, new_votes);
+        emit_event(
+            sdk,
+            DelegateVotesChanged {
+                delegate: to,
+                previousVotes: previous_votes,
+                newVotes: new_votes,
+            },
+        );
+    }
+}
+
+/// Only past blocks can be queried.
+fn past_value<SDK: SharedAPI>(
+    sdk: &SDK,
+    trace: Trace,
+    timepoint: U256,
+) -> Result<U256, TokenError> {
+    let now = clock(sdk);
+    if timepoint >= U256::from(now) {
+        return Err(TokenError::FutureLookup {
+            timepoint,
+            clock: now,
+        });
+    }
+    Ok(trace.upper_lookup(sdk, timepoint))
+}